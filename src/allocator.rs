@@ -0,0 +1,214 @@
+extern crate num;
+
+use self::num::traits::{Num};
+use self::num::traits::{CheckedAdd, CheckedSub, One, Zero};
+use common::{mid};
+use std::cmp::{max};
+
+/// Hands out runs of consecutive free points from [lower_bound, upper_bound],
+/// always picking the leftmost run which is long enough (first fit).
+/// Lengths are stored as N, so the number of points in the bounds,
+/// upper_bound - lower_bound + 1, must fit in N: e.g. u8 allows at most 255
+/// points, not [0, 255].
+pub struct Allocator<N>{
+    root: Node<N>,
+    lower_bound: N,
+    upper_bound: N,
+}
+
+// Longest free prefix, suffix and run anywhere in [start, end]. A node
+// without sons is uniform - either entirely free or entirely used; sons
+// are created only when a part of such node changes.
+#[derive(PartialEq, Eq, Debug)]
+struct Node<N> {
+    start: N,
+    end: N,
+    prefix: N,
+    suffix: N,
+    best: N,
+    left: Option<Box<Node<N>>>,
+    right: Option<Box<Node<N>>>,
+}
+
+impl<N: Num+Clone+Ord+CheckedAdd+CheckedSub> Allocator<N> {
+    pub fn new(lower_bound: N, upper_bound: N) -> Self {
+        if upper_bound < lower_bound {
+            panic!("Invalid bounds (lower_bound must not be greater than upper_bound)");
+        }
+        let len = upper_bound.checked_sub(&lower_bound)
+            .and_then(|d| d.checked_add(&One::one()));
+        if len.is_none() {
+            panic!("Invalid bounds (the number of points must fit in the bound type)");
+        }
+        Allocator {
+            root: Node::new(lower_bound.clone(), upper_bound.clone(), true),
+            lower_bound,
+            upper_bound,
+        }
+    }
+
+    pub fn bounds(&self) -> (N, N) {
+        (self.lower_bound.clone(), self.upper_bound.clone())
+    }
+
+    /// Finds the leftmost run of `len` free points, marks it used and
+    /// returns its first point.
+    pub fn allocate(&mut self, len: N) -> Option<N> {
+        if len <= Zero::zero() {
+            panic!("Can't allocate an empty run");
+        }
+        if self.root.best < len {
+            return None;
+        }
+        let start = self.root.find(&len);
+        let end = start.clone() + (len - One::one());
+        self.root.set(&start, &end, false);
+        Some(start)
+    }
+
+    /// Marks [start, start + len - 1] as used, whatever its state was.
+    pub fn reserve(&mut self, start: N, len: N) {
+        let end = self.run_end(&start, len);
+        self.root.set(&start, &end, false);
+    }
+
+    /// Marks [start, start + len - 1] as free, whatever its state was.
+    pub fn free(&mut self, start: N, len: N) {
+        let end = self.run_end(&start, len);
+        self.root.set(&start, &end, true);
+    }
+
+    pub fn is_free(&self, point: N) -> Option<bool> {
+        if point < self.lower_bound || point > self.upper_bound {
+            None
+        } else {
+            Some(self.root.is_free(&point))
+        }
+    }
+
+    /// Length of the longest run of free points.
+    pub fn largest_free(&self) -> N {
+        self.root.best.clone()
+    }
+
+    fn run_end(&self, start: &N, len: N) -> N {
+        if len <= Zero::zero() {
+            panic!("Invalid run (len must be positive)");
+        }
+        // compare before adding, start + len may not fit in N
+        if *start < self.lower_bound || *start > self.upper_bound
+            || len > self.upper_bound.clone() - start.clone() + One::one() {
+            panic!("Can't change a run outside of bounds");
+        }
+        start.clone() + (len - One::one())
+    }
+}
+
+impl<N: Num+Clone+Ord> Node<N> {
+    fn new(start: N, end: N, free: bool) -> Self {
+        let len = if free {
+            end.clone() - start.clone() + One::one()
+        } else {
+            Zero::zero()
+        };
+        Node {
+            start,
+            end,
+            prefix: len.clone(),
+            suffix: len.clone(),
+            best: len,
+            left: None,
+            right: None,
+        }
+    }
+
+    fn new_son(start: N, end: N, free: bool) -> Option<Box<Self>> {
+        Some(Box::new(Node::new(start, end, free)))
+    }
+
+    fn len(&self) -> N {
+        self.end.clone() - self.start.clone() + One::one()
+    }
+
+    fn find(&self, len: &N) -> N {
+        match (self.left.as_ref(), self.right.as_ref()) {
+            (Some(l), Some(r)) => {
+                if l.best >= *len {
+                    l.find(len)
+                } else if l.suffix.clone() + r.prefix.clone() >= *len {
+                    l.end.clone() - l.suffix.clone() + One::one()
+                } else {
+                    r.find(len)
+                }
+            },
+            // uniform and long enough, so entirely free
+            _ => self.start.clone()
+        }
+    }
+
+    fn is_free(&self, point: &N) -> bool {
+        let mid_n = mid(self.start.clone(), self.end.clone());
+        let son = if *point <= mid_n {
+            &self.left
+        } else {
+            &self.right
+        };
+        match son.as_ref() {
+            None => self.best > Zero::zero(),
+            Some(n) => n.is_free(point)
+        }
+    }
+
+    fn set(&mut self, start_s: &N, end_s: &N, free: bool) {
+        if *end_s < self.start || *start_s > self.end {
+            return;
+        }
+        if *start_s <= self.start && self.end <= *end_s {
+            *self = Node::new(self.start.clone(), self.end.clone(), free);
+            return;
+        }
+        let mid_n = mid(self.start.clone(), self.end.clone());
+        if self.left.is_none() {
+            let was_free = self.best > Zero::zero();
+            self.left = Node::new_son(self.start.clone(), mid_n.clone(), was_free);
+            self.right = Node::new_son(mid_n + One::one(), self.end.clone(), was_free);
+        }
+        if let Some(n) = self.left.as_mut() {
+            n.set(start_s, end_s, free);
+        }
+        if let Some(n) = self.right.as_mut() {
+            n.set(start_s, end_s, free);
+        }
+        self.update();
+    }
+
+    fn update(&mut self) {
+        let (prefix, suffix, best, uniform) = {
+            let l = self.left.as_ref().unwrap();
+            let r = self.right.as_ref().unwrap();
+            let prefix = if l.prefix == l.len() {
+                l.prefix.clone() + r.prefix.clone()
+            } else {
+                l.prefix.clone()
+            };
+            let suffix = if r.suffix == r.len() {
+                r.suffix.clone() + l.suffix.clone()
+            } else {
+                r.suffix.clone()
+            };
+            let best = max(max(l.best.clone(), r.best.clone()),
+                           l.suffix.clone() + r.prefix.clone());
+            let uniform = l.left.is_none() && r.left.is_none()
+                && (l.best > Zero::zero()) == (r.best > Zero::zero());
+            (prefix, suffix, best, uniform)
+        };
+        self.prefix = prefix;
+        self.suffix = suffix;
+        self.best = best;
+        if uniform {
+            // both sons entirely free or entirely used - forget them
+            self.left = None;
+            self.right = None;
+        }
+    }
+}
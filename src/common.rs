@@ -2,6 +2,7 @@ extern crate num;
 
 use self::num::traits::{Num};
 use self::num::traits::{One};
+use view::{NodeView};
 
 pub fn mid<N: Clone+Num>(start: N, end: N) -> N {
     let one1:N = One::one();
@@ -11,3 +12,18 @@ pub fn mid<N: Clone+Num>(start: N, end: N) -> N {
     // (start + end) / two
     start.clone() + (end - start) / two
 }
+
+// The canonical nodes of [start_s, end_s] below a missing node [start, end].
+pub fn unallocated<'a, N: Num+Clone+Ord, S>(start: N, end: N, start_s: &N, end_s: &N,
+                                        default: &'a S, acc: &mut Vec<NodeView<'a, N, S>>) {
+    if *end_s < start || *start_s > end {
+        return;
+    }
+    if *start_s <= start && end <= *end_s {
+        acc.push(NodeView::unallocated(start, end, default));
+        return;
+    }
+    let mid_n = mid(start.clone(), end.clone());
+    unallocated(start, mid_n.clone(), start_s, end_s, default, acc);
+    unallocated(mid_n + One::one(), end, start_s, end_s, default, acc);
+}
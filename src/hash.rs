@@ -0,0 +1,91 @@
+//! Polynomial rolling hash of a sequence of points, for comparing ranges of
+//! a PointSegmentTree keyed by position:
+//!
+//!   PointSegmentTree::new(0, n, RollingHash::identity(),
+//!                         Box::new(RollingHash::combine))
+//!
+//! Two moduli are used, so accidental collisions are very unlikely (but
+//! still possible).
+
+extern crate num;
+
+use self::num::traits::{Num};
+use pointsegment::{PointSegmentTree};
+
+const MOD1: u64 = 1_000_000_007;
+const MOD2: u64 = 998_244_353;
+const BASE1: u64 = 911_382_323;
+const BASE2: u64 = 972_663_749;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct RollingHash {
+    value1: u64,
+    value2: u64,
+    // BASE^len, modulo each of the moduli
+    power1: u64,
+    power2: u64,
+    len: u64,
+}
+
+impl RollingHash {
+    pub fn identity() -> Self {
+        RollingHash { value1: 0, value2: 0, power1: 1, power2: 1, len: 0 }
+    }
+
+    /// Hash of the one-point sequence `value`. Any u64 is accepted: the
+    /// value is mixed before it is reduced by the moduli, so values which
+    /// differ by a multiple of MOD1 * MOD2 don't always collide.
+    pub fn of(value: u64) -> Self {
+        let mixed = mix(value);
+        RollingHash {
+            value1: mixed % MOD1,
+            value2: mixed % MOD2,
+            power1: BASE1,
+            power2: BASE2,
+            len: 1,
+        }
+    }
+
+    /// Hash of the sequence `x` followed by the sequence `y`.
+    pub fn combine(x: &Self, y: &Self) -> Self {
+        RollingHash {
+            value1: (x.value1 * y.power1 + y.value1) % MOD1,
+            value2: (x.value2 * y.power2 + y.value2) % MOD2,
+            power1: x.power1 * y.power1 % MOD1,
+            power2: x.power2 * y.power2 % MOD2,
+            len: x.len + y.len,
+        }
+    }
+
+    /// Number of hashed points.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+// The splitmix64 finalizer, a bijection on u64.
+fn mix(value: u64) -> u64 {
+    let mut z = value;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Whether the points in [a, b] and in [c, d] hold the same sequence of
+/// values. False if the ranges differ in length or any of them is invalid.
+/// Only inserted points are hashed, so every position should be set.
+pub fn ranges_equal<N: Num+Clone+Ord>(t: &PointSegmentTree<N, RollingHash>,
+                                     a: N, b: N, c: N, d: N) -> bool {
+    // ranges of different lengths differ even if their unset points hide it
+    if b < a || d < c || b.clone() - a.clone() != d.clone() - c.clone() {
+        return false;
+    }
+    match (t.query(a, b), t.query(c, d)) {
+        (Some(x), Some(y)) => x == y,
+        _ => false
+    }
+}
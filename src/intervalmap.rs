@@ -0,0 +1,113 @@
+extern crate num;
+
+use self::num::traits::{Num};
+use self::num::traits::{One};
+use std::collections::BTreeMap;
+use std::ops::Bound::{Excluded, Unbounded};
+
+/// Disjoint ranges [start, end] (ends included) mapped to values. Adjacent
+/// ranges with equal values are always merged, so the stored ranges are
+/// maximal.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct IntervalMap<N: Ord, V> {
+    // start -> (end, value)
+    ranges: BTreeMap<N, (N, V)>,
+}
+
+impl<N: Num+Clone+Ord, V: Clone+PartialEq> IntervalMap<N, V> {
+    pub fn new() -> Self {
+        IntervalMap {
+            ranges: BTreeMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.ranges.clear();
+    }
+
+    pub fn get(&self, point: N) -> Option<&V> {
+        self.ranges.range(..=point.clone()).next_back()
+            .and_then(|(_, (end, value))| {
+                if point <= *end { Some(value) } else { None }
+            })
+    }
+
+    /// Maps every point of [start, end] to `value`, overriding previous
+    /// values there.
+    pub fn insert(&mut self, start: N, end: N, value: V) {
+        if end < start {
+            panic!("Invalid range (start must not be greater than end)");
+        }
+        self.remove(start.clone(), end.clone());
+        let mut start = start;
+        let mut end = end;
+
+        let before = self.ranges.range(..start.clone()).next_back()
+            .map(|(s, (e, v))| (s.clone(), e.clone(), v == &value));
+        if let Some((s, e, same)) = before {
+            if same && e + One::one() == start {
+                self.ranges.remove(&s);
+                start = s;
+            }
+        }
+        // s > end, so end + 1 can't overflow here
+        let after = self.ranges.range((Excluded(end.clone()), Unbounded)).next()
+            .map(|(s, (e, v))| (s.clone(), e.clone(), v == &value));
+        if let Some((s, e, same)) = after {
+            if same && end.clone() + One::one() == s {
+                self.ranges.remove(&s);
+                end = e;
+            }
+        }
+        self.ranges.insert(start, (end, value));
+    }
+
+    /// Unmaps every point of [start, end].
+    pub fn remove(&mut self, start: N, end: N) {
+        if end < start {
+            panic!("Invalid range (start must not be greater than end)");
+        }
+        for s in self.overlapping(&start, &end) {
+            let (e, v) = self.ranges.remove(&s).unwrap();
+            if s < start {
+                self.ranges.insert(s, (start.clone() - One::one(), v.clone()));
+            }
+            if e > end {
+                self.ranges.insert(end.clone() + One::one(), (e, v));
+            }
+        }
+    }
+
+    /// The stored `(start, end, value)` ranges, in increasing order.
+    pub fn iter<'a>(&'a self) -> impl Iterator<Item=(&'a N, &'a N, &'a V)> + 'a {
+        self.ranges.iter().map(|(s, (e, v))| (s, e, v))
+    }
+
+    /// The stored ranges from the one containing `point` (or the first one
+    /// after it) onwards, in increasing order.
+    pub fn iter_from<'a>(&'a self, point: N) -> impl Iterator<Item=(&'a N, &'a N, &'a V)> + 'a {
+        let first = self.ranges.range(..=point.clone()).next_back()
+            .filter(|&(_, (e, _))| *e >= point)
+            .map(|(s, _)| s.clone())
+            .unwrap_or(point);
+        self.ranges.range(first..).map(|(s, (e, v))| (s, e, v))
+    }
+
+    // starts of the stored ranges intersecting [start, end]
+    fn overlapping(&self, start: &N, end: &N) -> Vec<N> {
+        let mut result: Vec<N> = self.ranges.range(..=end.clone()).rev()
+            .take_while(|&(_, (e, _))| e >= start)
+            .map(|(s, _)| s.clone())
+            .collect();
+        result.reverse();
+        result
+    }
+}
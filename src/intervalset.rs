@@ -0,0 +1,93 @@
+extern crate num;
+
+use self::num::traits::{Num};
+use self::num::traits::{One};
+use intervalmap::{IntervalMap};
+
+/// A set of points stored as maximal disjoint ranges [start, end].
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct IntervalSet<N: Ord> {
+    map: IntervalMap<N, ()>,
+}
+
+impl<N: Num+Clone+Ord> IntervalSet<N> {
+    pub fn new() -> Self {
+        IntervalSet {
+            map: IntervalMap::new(),
+        }
+    }
+
+    /// Number of maximal ranges in the set.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn insert(&mut self, start: N, end: N) {
+        self.map.insert(start, end, ());
+    }
+
+    pub fn remove(&mut self, start: N, end: N) {
+        self.map.remove(start, end);
+    }
+
+    pub fn contains(&self, point: N) -> bool {
+        self.map.get(point).is_some()
+    }
+
+    pub fn iter<'a>(&'a self) -> impl Iterator<Item=(&'a N, &'a N)> + 'a {
+        self.map.iter().map(|(s, e, _)| (s, e))
+    }
+
+    pub fn union(&self, other: &IntervalSet<N>) -> IntervalSet<N> {
+        let mut result = self.clone();
+        for (s, e) in other.iter() {
+            result.insert(s.clone(), e.clone());
+        }
+        result
+    }
+
+    pub fn difference(&self, other: &IntervalSet<N>) -> IntervalSet<N> {
+        let mut result = self.clone();
+        for (s, e) in other.iter() {
+            result.remove(s.clone(), e.clone());
+        }
+        result
+    }
+
+    /// Points of [lower, upper] which are not in the set.
+    pub fn complement(&self, lower: N, upper: N) -> IntervalSet<N> {
+        let mut result = IntervalSet::new();
+        for (s, e) in self.gaps(lower, upper) {
+            result.insert(s, e);
+        }
+        result
+    }
+
+    /// Maximal ranges of [lower, upper] not covered by the set, in
+    /// increasing order.
+    pub fn gaps(&self, lower: N, upper: N) -> Vec<(N, N)> {
+        if upper < lower {
+            panic!("Invalid range (lower must not be greater than upper)");
+        }
+        let mut result = Vec::new();
+        let mut cursor = lower.clone();
+        for (s, e, _) in self.map.iter_from(lower) {
+            if *s > upper {
+                break;
+            }
+            if *s > cursor {
+                result.push((cursor, s.clone() - One::one()));
+            }
+            if *e >= upper {
+                return result;
+            }
+            cursor = e.clone() + One::one();
+        }
+        result.push((cursor, upper));
+        result
+    }
+}
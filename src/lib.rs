@@ -1,3 +1,17 @@
 pub mod segmentpoint;
 pub mod pointsegment;
+pub mod paint;
+pub mod intervalmap;
+pub mod intervalset;
+pub mod allocator;
+pub mod summary;
+pub mod ops;
+pub mod matrix;
+pub mod hash;
+pub mod stats;
+pub mod sketch;
+pub mod mergesort;
+pub mod lichao;
+pub mod view;
+pub mod persistent;
 mod common;
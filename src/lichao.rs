@@ -0,0 +1,204 @@
+extern crate num;
+
+use self::num::traits::{Num};
+use self::num::traits::{One};
+use common::{mid};
+use std::mem;
+
+/// Keeps linear functions, each valid on some segment, and answers what is
+/// the largest (or smallest) of their values at a point.
+pub struct LiChaoTree<N>{
+    root: Node<N>,
+    lower_bound: N,
+    upper_bound: N,
+    maximize: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Line<N> {
+    pub slope: N,
+    pub intercept: N,
+}
+
+// Every node keeps at most one line - the best one at its middle among
+// the lines inserted at it; the others are pushed down to the half where
+// they may still win.
+#[derive(PartialEq, Eq, Debug)]
+struct Node<N> {
+    start: N,
+    end: N,
+    line: Option<Line<N>>,
+    left: Option<Box<Node<N>>>,
+    right: Option<Box<Node<N>>>,
+}
+
+impl<N: Num+Clone> Line<N> {
+    pub fn new(slope: N, intercept: N) -> Self {
+        Line { slope, intercept }
+    }
+
+    pub fn at(&self, x: &N) -> N {
+        self.slope.clone() * x.clone() + self.intercept.clone()
+    }
+}
+
+impl<N: Num+Clone+Ord> LiChaoTree<N> {
+    /// A tree answering the largest value at a point.
+    pub fn new_max(lower_bound: N, upper_bound: N) -> Self {
+        LiChaoTree::with_goal(lower_bound, upper_bound, true)
+    }
+
+    /// A tree answering the smallest value at a point.
+    pub fn new_min(lower_bound: N, upper_bound: N) -> Self {
+        LiChaoTree::with_goal(lower_bound, upper_bound, false)
+    }
+
+    fn with_goal(lower_bound: N, upper_bound: N, maximize: bool) -> Self {
+        if upper_bound < lower_bound {
+            panic!("Invalid bounds (lower_bound must not be greater than upper_bound)");
+        }
+        LiChaoTree {
+            root: Node::new(lower_bound.clone(), upper_bound.clone()),
+            lower_bound,
+            upper_bound,
+            maximize,
+        }
+    }
+
+    pub fn bounds(&self) -> (N, N) {
+        (self.lower_bound.clone(), self.upper_bound.clone())
+    }
+
+    /// Inserts `slope * x + intercept`, valid everywhere within bounds.
+    pub fn insert_line(&mut self, slope: N, intercept: N) {
+        let (lower, upper) = self.bounds();
+        self.insert_segment(lower, upper, slope, intercept);
+    }
+
+    /// Inserts `slope * x + intercept`, valid only for x in [start, end].
+    pub fn insert_segment(&mut self, start: N, end: N, slope: N, intercept: N) {
+        if start < self.lower_bound || end > self.upper_bound {
+            panic!("Can't insert outside of bounds");
+        }
+        if end < start {
+            panic!("Invalid segment (start must not be greater than end)");
+        }
+        self.root.insert(start, end, Line::new(slope, intercept), self.maximize);
+    }
+
+    /// The best value at `point` of the lines valid there; None if there
+    /// are none or the point is out of bounds.
+    pub fn query(&self, point: N) -> Option<N> {
+        if point > self.upper_bound || point < self.lower_bound {
+            None
+        } else {
+            self.root.query(&point, self.maximize, None)
+        }
+    }
+}
+
+fn better<N: Ord>(x: &N, y: &N, maximize: bool) -> bool {
+    if maximize { x > y } else { x < y }
+}
+
+impl<N: Num+Clone+Ord> Node<N> {
+    fn new(start: N, end: N) -> Self {
+        Node {
+            start,
+            end,
+            line: None,
+            left: None,
+            right: None,
+        }
+    }
+
+    fn new_son(start: N, end: N) -> Option<Box<Self>> {
+        Some(Box::new(Node::new(start, end)))
+    }
+
+    fn query(&self, point: &N, maximize: bool, acc: Option<N>) -> Option<N> {
+        let acc2 = match (acc, self.line.as_ref()) {
+            (Some(a), Some(l)) => {
+                let v = l.at(point);
+                Some(if better(&v, &a, maximize) { v } else { a })
+            },
+            (None, Some(l)) => Some(l.at(point)),
+            (a, None) => a,
+        };
+        let mid_n = mid(self.start.clone(), self.end.clone());
+        let son = if *point <= mid_n {
+            &self.left
+        } else {
+            &self.right
+        };
+        match son.as_ref() {
+            None => acc2,
+            Some(n) => n.query(point, maximize, acc2)
+        }
+    }
+
+    fn insert(&mut self, start_s: N, end_s: N, line: Line<N>, maximize: bool) {
+        if start_s == self.start && end_s == self.end {
+            self.add_line(line, maximize);
+            return;
+        }
+        let mid_n = mid(self.start.clone(), self.end.clone());
+        if start_s <= mid_n {
+            if self.left.is_none() {
+                self.left = Node::new_son(self.start.clone(), mid_n.clone());
+            }
+            let end_l = if end_s <= mid_n { end_s.clone() } else { mid_n.clone() };
+            if let Some(n) = self.left.as_mut() {
+                n.insert(start_s.clone(), end_l, line.clone(), maximize);
+            }
+        }
+        if end_s > mid_n {
+            if self.right.is_none() {
+                self.right = Node::new_son(mid_n.clone() + One::one(), self.end.clone());
+            }
+            let start_r = if start_s > mid_n { start_s } else { mid_n + One::one() };
+            if let Some(n) = self.right.as_mut() {
+                n.insert(start_r, end_s, line, maximize);
+            }
+        }
+    }
+
+    // Adds a line valid on the whole node.
+    fn add_line(&mut self, line: Line<N>, maximize: bool) {
+        let pushed = match self.line {
+            None => {
+                self.line = Some(line);
+                return;
+            },
+            Some(ref mut current) => {
+                let mid_n = mid(self.start.clone(), self.end.clone());
+                let mut line = line;
+                if better(&line.at(&mid_n), &current.at(&mid_n), maximize) {
+                    mem::swap(current, &mut line);
+                }
+                line
+            }
+        };
+        if self.start == self.end {
+            return;
+        }
+        // the loser at the middle can only win on one side of it
+        let mid_n = mid(self.start.clone(), self.end.clone());
+        let current = self.line.clone().unwrap();
+        if better(&pushed.at(&self.start), &current.at(&self.start), maximize) {
+            if self.left.is_none() {
+                self.left = Node::new_son(self.start.clone(), mid_n);
+            }
+            if let Some(n) = self.left.as_mut() {
+                n.add_line(pushed, maximize);
+            }
+        } else if better(&pushed.at(&self.end), &current.at(&self.end), maximize) {
+            if self.right.is_none() {
+                self.right = Node::new_son(mid_n + One::one(), self.end.clone());
+            }
+            if let Some(n) = self.right.as_mut() {
+                n.add_line(pushed, maximize);
+            }
+        }
+    }
+}
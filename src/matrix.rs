@@ -0,0 +1,145 @@
+//! Square D x D matrices over a semiring, for dynamic programs where every
+//! point is a transition matrix. PointSegmentTree::query folds the points
+//! from left to right, so with
+//!
+//!   PointSegmentTree::new(0, n, Matrix::identity(), Box::new(Matrix::combine))
+//!
+//! query(l, r) is the ordered product M_l * M_(l+1) * ... * M_r of the
+//! inserted matrices.
+
+extern crate num;
+
+use self::num::traits::{Bounded, CheckedAdd, Num};
+use std::array;
+use std::fmt;
+use std::marker::{PhantomData};
+
+pub trait Semiring {
+    type Elem: Clone;
+    /// Identity of `add`, absorbing for `mul`.
+    fn zero() -> Self::Elem;
+    /// Identity of `mul`.
+    fn one() -> Self::Elem;
+    fn add(x: &Self::Elem, y: &Self::Elem) -> Self::Elem;
+    fn mul(x: &Self::Elem, y: &Self::Elem) -> Self::Elem;
+}
+
+/// The usual (+, *) semiring.
+pub struct Arithmetic<T>(PhantomData<T>);
+
+/// (min, +) semiring, with `T::max_value()` standing for infinity. Sums
+/// which overflow saturate, so too large ones become infinity.
+pub struct MinPlus<T>(PhantomData<T>);
+
+/// (max, +) semiring, with `T::min_value()` standing for minus infinity.
+/// Sums which overflow saturate, so too small ones become minus infinity.
+pub struct MaxPlus<T>(PhantomData<T>);
+
+impl<T: Num+Clone> Semiring for Arithmetic<T> {
+    type Elem = T;
+    fn zero() -> T { T::zero() }
+    fn one() -> T { T::one() }
+    fn add(x: &T, y: &T) -> T { x.clone() + y.clone() }
+    fn mul(x: &T, y: &T) -> T { x.clone() * y.clone() }
+}
+
+impl<T: Num+Bounded+CheckedAdd+Ord+Clone> Semiring for MinPlus<T> {
+    type Elem = T;
+    fn zero() -> T { T::max_value() }
+    fn one() -> T { T::zero() }
+    fn add(x: &T, y: &T) -> T {
+        if y < x { y.clone() } else { x.clone() }
+    }
+    fn mul(x: &T, y: &T) -> T {
+        if *x == T::max_value() || *y == T::max_value() {
+            T::max_value()
+        } else {
+            saturating_add(x, y)
+        }
+    }
+}
+
+impl<T: Num+Bounded+CheckedAdd+Ord+Clone> Semiring for MaxPlus<T> {
+    type Elem = T;
+    fn zero() -> T { T::min_value() }
+    fn one() -> T { T::zero() }
+    fn add(x: &T, y: &T) -> T {
+        if y > x { y.clone() } else { x.clone() }
+    }
+    fn mul(x: &T, y: &T) -> T {
+        if *x == T::min_value() || *y == T::min_value() {
+            T::min_value()
+        } else {
+            saturating_add(x, y)
+        }
+    }
+}
+
+fn saturating_add<T: Num+Bounded+CheckedAdd+Ord>(x: &T, y: &T) -> T {
+    match x.checked_add(y) {
+        Some(sum) => sum,
+        None if *x > T::zero() => T::max_value(),
+        None => T::min_value(),
+    }
+}
+
+pub struct Matrix<S: Semiring, const D: usize> {
+    rows: [[S::Elem; D]; D],
+}
+
+impl<S: Semiring, const D: usize> Matrix<S, D> {
+    pub fn from_rows(rows: [[S::Elem; D]; D]) -> Self {
+        Matrix { rows }
+    }
+
+    pub fn identity() -> Self {
+        Matrix {
+            rows: array::from_fn(|i| array::from_fn(|j| {
+                if i == j { S::one() } else { S::zero() }
+            })),
+        }
+    }
+
+    pub fn get(&self, row: usize, column: usize) -> &S::Elem {
+        &self.rows[row][column]
+    }
+
+    pub fn rows(&self) -> &[[S::Elem; D]; D] {
+        &self.rows
+    }
+
+    pub fn mul(&self, other: &Self) -> Self {
+        Matrix {
+            rows: array::from_fn(|i| array::from_fn(|j| {
+                (0..D).fold(S::zero(), |acc, k| {
+                    S::add(&acc, &S::mul(&self.rows[i][k], &other.rows[k][j]))
+                })
+            })),
+        }
+    }
+
+    /// The product `x * y`; matrix multiplication is not commutative.
+    pub fn combine(x: &Self, y: &Self) -> Self {
+        x.mul(y)
+    }
+}
+
+impl<S: Semiring, const D: usize> Clone for Matrix<S, D> {
+    fn clone(&self) -> Self {
+        Matrix { rows: self.rows.clone() }
+    }
+}
+
+impl<S: Semiring, const D: usize> PartialEq for Matrix<S, D> where S::Elem: PartialEq {
+    fn eq(&self, other: &Self) -> bool {
+        self.rows == other.rows
+    }
+}
+
+impl<S: Semiring, const D: usize> Eq for Matrix<S, D> where S::Elem: Eq {}
+
+impl<S: Semiring, const D: usize> fmt::Debug for Matrix<S, D> where S::Elem: fmt::Debug {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Matrix").field("rows", &self.rows).finish()
+    }
+}
@@ -0,0 +1,120 @@
+use common::{mid};
+
+/// Static tree over the positions of a slice, each node keeping the sorted
+/// values of its positions. Ranges are inclusive, like in
+/// PointSegmentTree::query.
+pub struct MergeSortTree<T> {
+    root: Node<T>,
+    len: usize,
+}
+
+#[derive(PartialEq, Eq, Debug)]
+struct Node<T> {
+    start: usize,
+    end: usize,
+    sorted: Vec<T>,
+    left: Option<Box<Node<T>>>,
+    right: Option<Box<Node<T>>>,
+}
+
+impl<T: Ord+Clone> MergeSortTree<T> {
+    pub fn new(values: &[T]) -> Self {
+        if values.is_empty() {
+            panic!("Can't build a tree of no values");
+        }
+        MergeSortTree {
+            root: Node::new(values, 0, values.len() - 1),
+            len: values.len(),
+        }
+    }
+
+    pub fn bounds(&self) -> (usize, usize) {
+        (0, self.len - 1)
+    }
+
+    /// Number of positions in [start_q, end_q] with value in [low, high].
+    pub fn count_in_range(&self, start_q: usize, end_q: usize, low: &T, high: &T) -> Option<usize> {
+        if end_q < start_q || end_q >= self.len {
+            return None;
+        }
+        if high < low {
+            return Some(0);
+        }
+        let mut nodes = Vec::new();
+        self.root.canonical(start_q, end_q, &mut nodes);
+        Some(nodes.iter().map(|n| {
+            n.sorted.partition_point(|v| v <= high) - n.sorted.partition_point(|v| v < low)
+        }).sum())
+    }
+
+    /// The k-th smallest value (counting from 0) among positions in
+    /// [start_q, end_q].
+    pub fn kth_smallest(&self, start_q: usize, end_q: usize, k: usize) -> Option<T> {
+        if end_q < start_q || end_q >= self.len || k > end_q - start_q {
+            return None;
+        }
+        let mut nodes = Vec::new();
+        self.root.canonical(start_q, end_q, &mut nodes);
+        let not_greater = |value: &T| -> usize {
+            nodes.iter().map(|n| n.sorted.partition_point(|v| v <= value)).sum()
+        };
+        // the answer is the first of all values with more than k values
+        // not greater than it in the range
+        let all = &self.root.sorted;
+        let index = all.partition_point(|v| not_greater(v) <= k);
+        Some(all[index].clone())
+    }
+}
+
+impl<T: Ord+Clone> Node<T> {
+    fn new(values: &[T], start: usize, end: usize) -> Self {
+        if start == end {
+            return Node {
+                start,
+                end,
+                sorted: vec![values[start].clone()],
+                left: None,
+                right: None,
+            };
+        }
+        let mid = mid(start, end);
+        let left = Node::new(values, start, mid);
+        let right = Node::new(values, mid + 1, end);
+        let mut sorted = Vec::with_capacity(end - start + 1);
+        let (mut i, mut j) = (0, 0);
+        while i < left.sorted.len() && j < right.sorted.len() {
+            if right.sorted[j] < left.sorted[i] {
+                sorted.push(right.sorted[j].clone());
+                j += 1;
+            } else {
+                sorted.push(left.sorted[i].clone());
+                i += 1;
+            }
+        }
+        sorted.extend_from_slice(&left.sorted[i..]);
+        sorted.extend_from_slice(&right.sorted[j..]);
+        Node {
+            start,
+            end,
+            sorted,
+            left: Some(Box::new(left)),
+            right: Some(Box::new(right)),
+        }
+    }
+
+    fn canonical<'a>(&'a self, start_q: usize, end_q: usize, acc: &mut Vec<&'a Node<T>>) {
+        if end_q < self.start || start_q > self.end {
+            return;
+        }
+        if start_q <= self.start && self.end <= end_q {
+            acc.push(self);
+            return;
+        }
+        if let Some(ref n) = self.left {
+            n.canonical(start_q, end_q, acc);
+        }
+        if let Some(ref n) = self.right {
+            n.canonical(start_q, end_q, acc);
+        }
+    }
+}
@@ -0,0 +1,160 @@
+//! Standard operations, each with an identity matching its combine. Use
+//! identity() as the tree default and combine as the tree operation:
+//!
+//!   PointSegmentTree::new(0, 100, Max::identity(), Box::new(Max::combine))
+//!   SegmentPointTree::new(0, 100, Sum::identity(), Box::new(Sum::combine))
+
+extern crate num;
+
+use self::num::traits::{Bounded, Float, One, Zero};
+use self::num::integer::{Integer};
+use std::ops::{Add, BitAnd as BitAndOp, BitOr as BitOrOp, BitXor, Mul, Not};
+
+pub trait Operation<T> {
+    fn identity() -> T;
+    fn combine(x: &T, y: &T) -> T;
+}
+
+pub struct Sum;
+pub struct Product;
+/// Minimum of totally ordered values, e.g. integers; see FloatMin for floats.
+pub struct Min;
+/// Maximum of totally ordered values, e.g. integers; see FloatMax for floats.
+pub struct Max;
+/// Minimum of floats, with +infinity as the identity.
+pub struct FloatMin;
+/// Maximum of floats, with -infinity as the identity.
+pub struct FloatMax;
+pub struct Gcd;
+pub struct Lcm;
+pub struct BitAnd;
+pub struct BitOr;
+pub struct Xor;
+/// Number of points - insert 1 for every present point.
+pub struct Count;
+/// Smallest of totally ordered values with its position,
+/// `Option<(value, position)>`; ties go to the smaller position.
+pub struct MinWithIndex;
+/// Largest of totally ordered values with its position,
+/// `Option<(value, position)>`; ties go to the smaller position.
+pub struct MaxWithIndex;
+/// Value of the first point, `Option<value>`.
+pub struct First;
+/// Value of the last point, `Option<value>`.
+pub struct Last;
+
+impl<T: Zero+Add<Output=T>+Clone> Operation<T> for Sum {
+    fn identity() -> T { T::zero() }
+    fn combine(x: &T, y: &T) -> T { x.clone() + y.clone() }
+}
+
+impl<T: One+Mul<Output=T>+Clone> Operation<T> for Product {
+    fn identity() -> T { T::one() }
+    fn combine(x: &T, y: &T) -> T { x.clone() * y.clone() }
+}
+
+impl<T: Bounded+Ord+Clone> Operation<T> for Min {
+    fn identity() -> T { T::max_value() }
+    fn combine(x: &T, y: &T) -> T {
+        if y < x { y.clone() } else { x.clone() }
+    }
+}
+
+impl<T: Bounded+Ord+Clone> Operation<T> for Max {
+    fn identity() -> T { T::min_value() }
+    fn combine(x: &T, y: &T) -> T {
+        if y > x { y.clone() } else { x.clone() }
+    }
+}
+
+impl<T: Float> Operation<T> for FloatMin {
+    fn identity() -> T { T::infinity() }
+    fn combine(x: &T, y: &T) -> T {
+        if y < x { *y } else { *x }
+    }
+}
+
+impl<T: Float> Operation<T> for FloatMax {
+    fn identity() -> T { T::neg_infinity() }
+    fn combine(x: &T, y: &T) -> T {
+        if y > x { *y } else { *x }
+    }
+}
+
+impl<T: Integer+Clone> Operation<T> for Gcd {
+    fn identity() -> T { T::zero() }
+    fn combine(x: &T, y: &T) -> T { x.gcd(y) }
+}
+
+impl<T: Integer+Clone> Operation<T> for Lcm {
+    fn identity() -> T { T::one() }
+    fn combine(x: &T, y: &T) -> T { x.lcm(y) }
+}
+
+impl<T: Zero+Not<Output=T>+BitAndOp<Output=T>+Clone> Operation<T> for BitAnd {
+    fn identity() -> T { !T::zero() }
+    fn combine(x: &T, y: &T) -> T { x.clone() & y.clone() }
+}
+
+impl<T: Zero+BitOrOp<Output=T>+Clone> Operation<T> for BitOr {
+    fn identity() -> T { T::zero() }
+    fn combine(x: &T, y: &T) -> T { x.clone() | y.clone() }
+}
+
+impl<T: Zero+BitXor<Output=T>+Clone> Operation<T> for Xor {
+    fn identity() -> T { T::zero() }
+    fn combine(x: &T, y: &T) -> T { x.clone() ^ y.clone() }
+}
+
+impl Operation<usize> for Count {
+    fn identity() -> usize { 0 }
+    fn combine(x: &usize, y: &usize) -> usize { x + y }
+}
+
+impl<V: Ord+Clone, N: Ord+Clone> Operation<Option<(V, N)>> for MinWithIndex {
+    fn identity() -> Option<(V, N)> { None }
+    fn combine(x: &Option<(V, N)>, y: &Option<(V, N)>) -> Option<(V, N)> {
+        match (x, y) {
+            (Some(a), Some(b)) => {
+                if b.0 < a.0 || (b.0 == a.0 && b.1 < a.1) {
+                    y.clone()
+                } else {
+                    x.clone()
+                }
+            },
+            (None, _) => y.clone(),
+            (_, None) => x.clone(),
+        }
+    }
+}
+
+impl<V: Ord+Clone, N: Ord+Clone> Operation<Option<(V, N)>> for MaxWithIndex {
+    fn identity() -> Option<(V, N)> { None }
+    fn combine(x: &Option<(V, N)>, y: &Option<(V, N)>) -> Option<(V, N)> {
+        match (x, y) {
+            (Some(a), Some(b)) => {
+                if b.0 > a.0 || (b.0 == a.0 && b.1 < a.1) {
+                    y.clone()
+                } else {
+                    x.clone()
+                }
+            },
+            (None, _) => y.clone(),
+            (_, None) => x.clone(),
+        }
+    }
+}
+
+impl<V: Clone> Operation<Option<V>> for First {
+    fn identity() -> Option<V> { None }
+    fn combine(x: &Option<V>, y: &Option<V>) -> Option<V> {
+        x.clone().or_else(|| y.clone())
+    }
+}
+
+impl<V: Clone> Operation<Option<V>> for Last {
+    fn identity() -> Option<V> { None }
+    fn combine(x: &Option<V>, y: &Option<V>) -> Option<V> {
+        y.clone().or_else(|| x.clone())
+    }
+}
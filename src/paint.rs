@@ -0,0 +1,184 @@
+extern crate num;
+
+use self::num::traits::{Num};
+use self::num::traits::{One};
+use common::{mid};
+use std::cmp::{max, min};
+use std::vec;
+
+pub struct PaintTree<N, V>{
+    root: Node<N, V>,
+    lower_bound: N,
+    upper_bound: N,
+    default: V,
+}
+
+#[derive(PartialEq, Eq, Debug)]
+struct Node<N, V> {
+    start: N,
+    end: N,
+    // Some(v) means every point of the node is painted v and the node
+    // has no sons; None means the sons decide (a missing son is unpainted)
+    value: Option<V>,
+    left: Option<Box<Node<N, V>>>,
+    right: Option<Box<Node<N, V>>>,
+}
+
+pub type Runs<N, V> = vec::IntoIter<(N, N, V)>;
+
+impl<N: Num+Clone+Ord, V: Clone+PartialEq> PaintTree<N, V> {
+    pub fn new(lower_bound: N, upper_bound: N, default_value: V) -> Self {
+        if upper_bound < lower_bound {
+            panic!("Invalid bounds (lower_bound must not be greater than upper_bound)");
+        }
+        PaintTree {
+            root: Node::new(lower_bound.clone(), upper_bound.clone(), None),
+            lower_bound,
+            upper_bound,
+            default: default_value,
+        }
+    }
+
+    pub fn bounds(&self) -> (N, N) {
+        (self.lower_bound.clone(), self.upper_bound.clone())
+    }
+
+    /// Sets every point of [start, end] to `value`, overriding whatever
+    /// was assigned there before.
+    pub fn assign(&mut self, start: N, end: N, value: V) {
+        if start < self.lower_bound || end > self.upper_bound {
+            panic!("Can't assign outside of bounds");
+        }
+        if end < start {
+            panic!("Invalid segment (start must not be greater than end)");
+        }
+        self.root.assign(start, end, value);
+    }
+
+    pub fn query(&self, point: N) -> Option<V> {
+        if point > self.upper_bound || point < self.lower_bound {
+            None
+        } else {
+            Some(self.root.query(point).unwrap_or(&self.default).clone())
+        }
+    }
+
+    /// Maximal `(start, end, value)` pieces of constant value covering
+    /// [start_q, end_q], in increasing order.
+    pub fn runs(&self, start_q: N, end_q: N) -> Option<Runs<N, V>> {
+        if end_q < start_q || start_q < self.lower_bound || end_q > self.upper_bound {
+            None
+        } else {
+            let mut acc = Vec::new();
+            self.root.runs(&start_q, &end_q, &self.default, &mut acc);
+            Some(acc.into_iter())
+        }
+    }
+}
+
+fn push_run<N: Num+Clone+Ord, V: Clone+PartialEq>(acc: &mut Vec<(N, N, V)>,
+                                                  start: N, end: N, value: &V) {
+    if let Some(last) = acc.last_mut() {
+        if last.2 == *value && last.1.clone() + One::one() == start {
+            last.1 = end;
+            return;
+        }
+    }
+    acc.push((start, end, value.clone()));
+}
+
+impl<N: Num+Clone+Ord, V: Clone+PartialEq> Node<N, V> {
+    fn new(start: N, end: N, value: Option<V>) -> Self {
+        Node {
+            start,
+            end,
+            value,
+            left: None,
+            right: None,
+        }
+    }
+
+    fn new_son(start: N, end: N, value: Option<V>) -> Option<Box<Self>> {
+        Some(Box::new(Node::new(start, end, value)))
+    }
+
+    fn query(&self, point: N) -> Option<&V> {
+        if self.value.is_some() {
+            return self.value.as_ref();
+        }
+        let mid_n = mid(self.start.clone(), self.end.clone());
+        let son = if point <= mid_n {
+            &self.left
+        } else {
+            &self.right
+        };
+        son.as_ref().and_then(|n| n.query(point))
+    }
+
+    fn assign(&mut self, start_s: N, end_s: N, value_s: V) {
+        if start_s == self.start && end_s == self.end {
+            self.value = Some(value_s);
+            self.left = None;
+            self.right = None;
+            return;
+        }
+        let mid_n = mid(self.start.clone(), self.end.clone());
+        if let Some(v) = self.value.take() {
+            // push the uniform value down before painting over a part of it
+            self.left = Node::new_son(self.start.clone(), mid_n.clone(), Some(v.clone()));
+            self.right = Node::new_son(mid_n.clone() + One::one(), self.end.clone(), Some(v));
+        }
+        if start_s <= mid_n {
+            if self.left.is_none() {
+                self.left = Node::new_son(self.start.clone(), mid_n.clone(), None);
+            }
+            if let Some(n) = self.left.as_mut() {
+                n.assign(start_s.clone(), min(end_s.clone(), mid_n.clone()), value_s.clone());
+            }
+        }
+        if end_s > mid_n {
+            if self.right.is_none() {
+                self.right = Node::new_son(mid_n.clone() + One::one(), self.end.clone(), None);
+            }
+            if let Some(n) = self.right.as_mut() {
+                n.assign(max(start_s, mid_n + One::one()), end_s, value_s);
+            }
+        }
+        // both sons painted the same - merge them back
+        let merged = match (self.left.as_ref(), self.right.as_ref()) {
+            (Some(l), Some(r)) if l.value.is_some() && l.value == r.value => l.value.clone(),
+            _ => None
+        };
+        if merged.is_some() {
+            self.value = merged;
+            self.left = None;
+            self.right = None;
+        }
+    }
+
+    fn runs(&self, start_q: &N, end_q: &N, default: &V, acc: &mut Vec<(N, N, V)>) {
+        let start = max(self.start.clone(), start_q.clone());
+        let end = min(self.end.clone(), end_q.clone());
+        if let Some(ref v) = self.value {
+            push_run(acc, start, end, v);
+            return;
+        }
+        if self.start == self.end {
+            push_run(acc, start, end, default);
+            return;
+        }
+        let mid_n = mid(self.start.clone(), self.end.clone());
+        if start <= mid_n {
+            match self.left {
+                None => push_run(acc, start.clone(), min(end.clone(), mid_n.clone()), default),
+                Some(ref n) => n.runs(start_q, end_q, default, acc)
+            }
+        }
+        if end > mid_n {
+            match self.right {
+                None => push_run(acc, max(start, mid_n + One::one()), end, default),
+                Some(ref n) => n.runs(start_q, end_q, default, acc)
+            }
+        }
+    }
+}
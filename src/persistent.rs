@@ -0,0 +1,215 @@
+extern crate num;
+
+use self::num::traits::{Num};
+use self::num::traits::{One};
+use common::{mid};
+pub use pointsegment::{F};
+use std::rc::{Rc};
+
+/// PointSegmentTree whose nodes are shared, never modified: every update
+/// copies only the path it changes, so `clone` is O(1) and old copies keep
+/// their contents. Sharing also lets `copy_range` reuse whole subtrees.
+pub struct PersistentPointSegmentTree<N, P> {
+    root: Link<P>,
+    lower_bound: N,
+    upper_bound: N,
+    default: P,
+    combine: Rc<F<P>>,
+}
+
+// None for subtrees without inserted points. Nodes don't know their range,
+// it is derived on the way down, so the same node can serve at different
+// places of the tree.
+type Link<P> = Option<Rc<Node<P>>>;
+
+#[derive(PartialEq, Eq, Debug)]
+struct Node<P> {
+    value: P,
+    left: Link<P>,
+    right: Link<P>,
+}
+
+impl<N: Clone, P: Clone> Clone for PersistentPointSegmentTree<N, P> {
+    fn clone(&self) -> Self {
+        PersistentPointSegmentTree {
+            root: self.root.clone(),
+            lower_bound: self.lower_bound.clone(),
+            upper_bound: self.upper_bound.clone(),
+            default: self.default.clone(),
+            combine: self.combine.clone(),
+        }
+    }
+}
+
+impl<N: Num+Clone+Ord, P: Clone> PersistentPointSegmentTree<N, P> {
+    pub fn new(lower_bound: N, upper_bound: N, default_value: P,
+               combine: Box<F<P>>) -> Self
+    {
+        if lower_bound > upper_bound {
+            panic!("Invalid bounds (lower_bound must not be greater than upper_bound)");
+        }
+        PersistentPointSegmentTree {
+            root: None,
+            lower_bound,
+            upper_bound,
+            default: default_value,
+            combine: Rc::from(combine),
+        }
+    }
+
+    pub fn bounds(&self) -> (N, N) {
+        (self.lower_bound.clone(), self.upper_bound.clone())
+    }
+
+    pub fn insert(&mut self, point_n: N, point_data: P) {
+        if point_n < self.lower_bound || point_n > self.upper_bound {
+            panic!("Attempted insert out of tree bounds");
+        }
+        self.root = insert(&self.root, self.lower_bound.clone(), self.upper_bound.clone(),
+                           &point_n, point_data, &*self.combine);
+    }
+
+    /// Same as PointSegmentTree::query.
+    pub fn query(&self, start_q: N, end_q: N) -> Option<P> {
+        if end_q < start_q || start_q < self.lower_bound || end_q > self.upper_bound {
+            None
+        } else {
+            Some(query(&self.root, self.lower_bound.clone(), self.upper_bound.clone(),
+                       &start_q, &end_q, &*self.combine, self.default.clone()))
+        }
+    }
+
+    /// Makes the points of [dst_l, dst_l + (src_r - src_l)] a copy of the
+    /// points of [src_l, src_r], as they were before the call; points not
+    /// inserted in the source become not inserted in the destination.
+    /// Parts of the source which sit in the tree the same way as their
+    /// destination (e.g. [0, 7] and [8, 15]) are shared instead of copied,
+    /// so aligned copies take O(log n) no matter how many points they hold.
+    pub fn copy_range(&mut self, src_l: N, src_r: N, dst_l: N) {
+        if src_r < src_l || src_l < self.lower_bound || src_r > self.upper_bound
+            || dst_l < self.lower_bound
+            || dst_l.clone() > self.upper_bound.clone() - (src_r.clone() - src_l.clone()) {
+            panic!("Attempted copy out of tree bounds");
+        }
+        let dst_r = dst_l.clone() + (src_r - src_l.clone());
+        let copy = RangeCopy {
+            source: &self.root,
+            lower_bound: &self.lower_bound,
+            upper_bound: &self.upper_bound,
+            src_l: &src_l,
+            dst_l: &dst_l,
+            dst_r: &dst_r,
+            combine: &*self.combine,
+        };
+        self.root = copy.apply(&self.root, self.lower_bound.clone(), self.upper_bound.clone());
+    }
+}
+
+fn join<P: Clone>(left: Link<P>, right: Link<P>, combine: &F<P>) -> Link<P> {
+    let value = match (left.as_ref(), right.as_ref()) {
+        (Some(l), Some(r)) => combine(&l.value, &r.value),
+        (Some(l), _) => l.value.clone(),
+        (_, Some(r)) => r.value.clone(),
+        _ => return None
+    };
+    Some(Rc::new(Node { value, left, right }))
+}
+
+fn sons<P>(node: &Link<P>) -> (Link<P>, Link<P>) {
+    match node.as_ref() {
+        None => (None, None),
+        Some(n) => (n.left.clone(), n.right.clone()),
+    }
+}
+
+fn insert<N: Num+Clone+Ord, P: Clone>(node: &Link<P>, start: N, end: N, point_n: &N,
+                                      point_data: P, combine: &F<P>) -> Link<P> {
+    if start == end {
+        return Some(Rc::new(Node { value: point_data, left: None, right: None }));
+    }
+    let mid = mid(start.clone(), end.clone());
+    let (left, right) = sons(node);
+    if *point_n <= mid {
+        let left = insert(&left, start, mid, point_n, point_data, combine);
+        join(left, right, combine)
+    } else {
+        let right = insert(&right, mid + One::one(), end, point_n, point_data, combine);
+        join(left, right, combine)
+    }
+}
+
+fn query<N: Num+Clone+Ord, P: Clone>(node: &Link<P>, start: N, end: N, start_q: &N, end_q: &N,
+                                     combine: &F<P>, acc: P) -> P {
+    let n = match node.as_ref() {
+        None => return acc,
+        Some(n) => n,
+    };
+    if *end_q < start || *start_q > end {
+        return acc;
+    }
+    if *start_q <= start && end <= *end_q {
+        return combine(&acc, &n.value);
+    }
+    let mid = mid(start.clone(), end.clone());
+    let acc = query(&n.left, start, mid.clone(), start_q, end_q, combine, acc);
+    query(&n.right, mid + One::one(), end, start_q, end_q, combine, acc)
+}
+
+// Everything copy_range needs on the way down.
+struct RangeCopy<'a, N: 'a, P: 'a> {
+    source: &'a Link<P>,
+    lower_bound: &'a N,
+    upper_bound: &'a N,
+    src_l: &'a N,
+    dst_l: &'a N,
+    dst_r: &'a N,
+    combine: &'a F<P>,
+}
+
+impl<'a, N: Num+Clone+Ord, P: Clone> RangeCopy<'a, N, P> {
+    // The node of range [start, end] after the copy.
+    fn apply(&self, node: &Link<P>, start: N, end: N) -> Link<P> {
+        if *self.dst_r < start || *self.dst_l > end {
+            return node.clone();
+        }
+        if *self.dst_l <= start && end <= *self.dst_r {
+            let start_s = start.clone() - self.dst_l.clone() + self.src_l.clone();
+            let end_s = end.clone() - self.dst_l.clone() + self.src_l.clone();
+            if let Some(found) = self.aligned(start_s, end_s) {
+                return found;
+            }
+        }
+        let mid = mid(start.clone(), end.clone());
+        let (left, right) = sons(node);
+        let left = self.apply(&left, start, mid.clone());
+        let right = self.apply(&right, mid + One::one(), end);
+        join(left, right, self.combine)
+    }
+
+    // The source node of range exactly [start_s, end_s], if the tree has
+    // one. A range inside a subtree without points is empty whether it is
+    // a node or not, so Some(None) then.
+    fn aligned(&self, start_s: N, end_s: N) -> Option<Link<P>> {
+        let mut node = self.source;
+        let (mut start, mut end) = (self.lower_bound.clone(), self.upper_bound.clone());
+        loop {
+            let n = match node.as_ref() {
+                None => return Some(None),
+                Some(n) => n,
+            };
+            if start == start_s && end == end_s {
+                return Some(node.clone());
+            }
+            let mid = mid(start.clone(), end.clone());
+            if end_s <= mid {
+                node = &n.left;
+                end = mid;
+            } else if start_s > mid {
+                node = &n.right;
+                start = mid + One::one();
+            } else {
+                return None;
+            }
+        }
+    }
+}
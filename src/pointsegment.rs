@@ -2,14 +2,21 @@ extern crate num;
 
 use self::num::traits::{Num};
 use self::num::traits::{One};
-use common::{mid};
+use common::{mid, unallocated};
+use view::{NodeView, TreeNode};
+use std::cmp::{max, min, Ordering};
+use std::collections::{BinaryHeap};
+use std::mem;
+use std::rc::{Rc};
 
 pub struct PointSegmentTree<N, P>{
     root: Node<N, P>,
     lower_bound: N, 
     upper_bound: N, 
     default: P,
-    combine: Box<F<P>>
+    // shared with the trees split off from this one
+    combine: Rc<F<P>>,
+    tie_break: TieBreak,
 }
 
 #[derive(PartialEq, Eq, Debug)]
@@ -17,11 +24,21 @@ struct Node<N, P> {
     start: N,
     end: N,
     value: P, 
+    // whether a point was inserted here, only kept for leaves; all leaves
+    // but the root of a single-point tree are created by inserts
+    inserted: bool,
     left: Option<Box<Node<N, P>>>,
     right: Option<Box<Node<N, P>>>,
 }
 
-pub type F<P> = Fn(&P, &P) -> P;
+pub type F<P> = dyn Fn(&P, &P) -> P;
+
+/// Which of the equally good points is reported by position queries.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TieBreak {
+    Leftmost,
+    Rightmost,
+}
 
 impl<N: Num+Clone+Ord, P: Clone> PointSegmentTree<N, P> {
     pub fn new(lower_bound: N, upper_bound: N, default_value: P,
@@ -36,10 +53,11 @@ impl<N: Num+Clone+Ord, P: Clone> PointSegmentTree<N, P> {
             
         PointSegmentTree {
             default:  default_value,
-            lower_bound: lower_bound,
-            upper_bound: upper_bound,
-            combine: combine,
-            root: node
+            lower_bound,
+            upper_bound,
+            combine: Rc::from(combine),
+            root: node,
+            tie_break: TieBreak::Leftmost,
         }
     }
 
@@ -51,6 +69,56 @@ impl<N: Num+Clone+Ord, P: Clone> PointSegmentTree<N, P> {
                          &*self.combine);
     }
 
+    /// Replaces the value of every inserted point in [start_m, end_m] by
+    /// `f(value)`, recomputing each affected node once.
+    pub fn map_range<M>(&mut self, start_m: N, end_m: N, mut f: M)
+        where M: FnMut(&P) -> P
+    {
+        if end_m < start_m || start_m < self.lower_bound || end_m > self.upper_bound {
+            panic!("Attempted map out of tree bounds");
+        }
+        self.root.map_range(&start_m, &end_m, &mut f, &*self.combine);
+    }
+
+    /// Unions the points of both trees, which must have the same bounds
+    /// (and, for trees made by `split_off`, be split off the same way).
+    /// Points inserted in both get `merge_leaf(self value, other value)`;
+    /// subtrees allocated in only one of the trees are moved as they are.
+    pub fn merge<M>(mut self, other: Self, merge_leaf: M) -> Self
+        where M: Fn(&P, &P) -> P
+    {
+        if self.bounds() != other.bounds()
+            || self.root.start != other.root.start || self.root.end != other.root.end {
+            panic!("Can't merge trees with different bounds");
+        }
+        self.root.merge(other.root, &merge_leaf, &*self.combine);
+        self
+    }
+
+    /// Moves the points after `k` to a new tree with bounds [k + 1,
+    /// upper_bound], leaving [lower_bound, k] in this one. Only the nodes on
+    /// the path to `k` are touched; both trees keep the node layout of the
+    /// original one.
+    pub fn split_off(&mut self, k: N) -> Self {
+        if k < self.lower_bound || k >= self.upper_bound {
+            panic!("Attempted split out of tree bounds");
+        }
+        let root = self.root.split_off(&k, &self.default, &*self.combine);
+        let upper_bound = mem::replace(&mut self.upper_bound, k.clone());
+        PointSegmentTree {
+            root,
+            lower_bound: k + One::one(),
+            upper_bound,
+            default: self.default.clone(),
+            combine: self.combine.clone(),
+            tie_break: self.tie_break,
+        }
+    }
+
+    /// Combines the default with the values of points in [start_q, end_q],
+    /// always from left to right: default, then points in increasing order of
+    /// keys. Points which were never inserted are skipped, so `combine` only
+    /// has to be associative, not commutative.
     pub fn query(&self, start_q: N, end_q: N) -> Option<P> {
         if end_q < start_q || start_q < self.lower_bound || end_q > self.upper_bound {
             None
@@ -63,14 +131,383 @@ impl<N: Num+Clone+Ord, P: Clone> PointSegmentTree<N, P> {
         (self.lower_bound.clone(), self.upper_bound.clone())
     }
 
+    /// Combines the points of all given ranges, in order of keys, in a
+    /// single traversal. None if any range is invalid or out of bounds, or
+    /// if the ranges overlap.
+    pub fn query_ranges(&self, ranges: &[(N, N)]) -> Option<P> {
+        let mut sorted: Vec<(N, N)> = Vec::with_capacity(ranges.len());
+        for (start_q, end_q) in ranges {
+            if end_q < start_q || *start_q < self.lower_bound || *end_q > self.upper_bound {
+                return None;
+            }
+            sorted.push((start_q.clone(), end_q.clone()));
+        }
+        sorted.sort_by(|x, y| x.0.cmp(&y.0));
+        let mut merged: Vec<(N, N)> = Vec::with_capacity(sorted.len());
+        for (start_q, end_q) in sorted {
+            if let Some(last) = merged.last_mut() {
+                if start_q <= last.1 {
+                    return None;
+                }
+                // touching ranges are queried as one
+                if last.1.clone() + One::one() == start_q {
+                    last.1 = end_q;
+                    continue;
+                }
+            }
+            merged.push((start_q, end_q));
+        }
+        Some(self.root.query_ranges(&merged, &*self.combine, self.default.clone()))
+    }
+
+    pub fn root(&self) -> NodeView<'_, N, P> {
+        NodeView::new(&self.root)
+    }
+
+    /// The canonical nodes covering [start_q, end_q], in order; `query`
+    /// combines exactly their values. Those the tree doesn't have yet are
+    /// reported as unallocated, holding the default.
+    pub fn decompose(&self, start_q: N, end_q: N) -> Option<Vec<NodeView<'_, N, P>>> {
+        if end_q < start_q || start_q < self.lower_bound || end_q > self.upper_bound {
+            return None;
+        }
+        let mut nodes = Vec::new();
+        self.root.views(&start_q, &end_q, &self.default, &mut nodes);
+        Some(nodes)
+    }
+
+    /// Combines all points within bounds except those in [start_q, end_q].
+    pub fn query_complement(&self, start_q: N, end_q: N) -> Option<P> {
+        if end_q < start_q || start_q < self.lower_bound || end_q > self.upper_bound {
+            return None;
+        }
+        let mut ranges = Vec::new();
+        if start_q > self.lower_bound {
+            ranges.push((self.lower_bound.clone(), start_q - One::one()));
+        }
+        if end_q < self.upper_bound {
+            ranges.push((end_q + One::one(), self.upper_bound.clone()));
+        }
+        self.query_ranges(&ranges)
+    }
+
+    /// Lazily yields the inserted points of [start_q, end_q] for which
+    /// `leaf_matches` holds, in increasing order. Whole subtrees are skipped
+    /// when `node_may_contain` is false for their combined value, e.g. for
+    /// max and "value > t" use `|m| *m > t` for both.
+    pub fn report<C, M>(&self, start_q: N, end_q: N,
+                        node_may_contain: C, leaf_matches: M) -> Option<Report<'_, N, P, C, M>>
+        where C: FnMut(&P) -> bool, M: FnMut(&P) -> bool
+    {
+        if end_q < start_q || start_q < self.lower_bound || end_q > self.upper_bound {
+            return None;
+        }
+        Some(Report {
+            stack: vec![&self.root],
+            start_q,
+            end_q,
+            node_may_contain,
+            leaf_matches,
+        })
+    }
+
+    /// The last inserted point at or before `point_n`.
+    pub fn predecessor(&self, point_n: N) -> Option<(N, P)> {
+        self.root.last_until(&point_n, &|_| true).map(Node::point)
+    }
+
+    /// The first inserted point at or after `point_n`.
+    pub fn successor(&self, point_n: N) -> Option<(N, P)> {
+        self.root.first_from(&point_n, &|_| true).map(Node::point)
+    }
+
+    pub fn first(&self) -> Option<(N, P)> {
+        self.successor(self.lower_bound.clone())
+    }
+
+    pub fn last(&self) -> Option<(N, P)> {
+        self.predecessor(self.upper_bound.clone())
+    }
+
+    /// Running aggregates over [start_q, end_q] from left to right: one item
+    /// per inserted point, and one per maximal stretch of points which were
+    /// never inserted. Each item carries `query(start_q, x)` for its last
+    /// point x.
+    ///
+    /// Nodes aren't path-compressed, so the walk passes every allocated
+    /// ancestor of the k points in range: O(k log U) in total for a tree
+    /// over U points, not O(k + log U).
+    pub fn scan(&self, start_q: N, end_q: N) -> Option<Scan<'_, N, P>> {
+        if end_q < start_q || start_q < self.lower_bound || end_q > self.upper_bound {
+            return None;
+        }
+        Some(Scan {
+            stack: vec![Pending::Node(&self.root)],
+            gap: None,
+            acc: self.default.clone(),
+            combine: &*self.combine,
+            start_q,
+            end_q,
+        })
+    }
+
+    pub fn tie_break(&self) -> TieBreak {
+        self.tie_break
+    }
+
+    pub fn set_tie_break(&mut self, tie_break: TieBreak) {
+        self.tie_break = tie_break;
+    }
+}
+
+impl<N: Num+Clone+Ord, P: Clone+PartialEq> PointSegmentTree<N, P> {
+    /// For order-based operations (where `combine` always returns one of
+    /// its arguments, like max or min) finds the inserted point in
+    /// [start_q, end_q] holding the combined value. Points which were never
+    /// inserted are not considered, so the result is None if there are none.
+    pub fn query_with_position(&self, start_q: N, end_q: N) -> Option<(N, P)> {
+        if end_q < start_q || start_q < self.lower_bound || end_q > self.upper_bound {
+            return None;
+        }
+        let mut nodes = Vec::new();
+        self.root.canonical(&start_q, &end_q, &mut nodes);
+        let best = match nodes.split_first() {
+            None => return None,
+            Some((first, rest)) => rest.iter().fold(first.value.clone(),
+                                                    |acc, n| (self.combine)(&acc, &n.value))
+        };
+        let found = match self.tie_break {
+            TieBreak::Leftmost => nodes.iter().find(|n| n.value == best),
+            TieBreak::Rightmost => nodes.iter().rev().find(|n| n.value == best),
+        };
+        found.map(|n| n.position_of(&best, self.tie_break))
+    }
+
+    /// The last point at or before `point_n` with a value other than the
+    /// default. Inserted points holding the default are skipped one by one.
+    pub fn predecessor_non_default(&self, point_n: N) -> Option<(N, P)> {
+        let default = &self.default;
+        self.root.last_until(&point_n, &|v| v != default).map(Node::point)
+    }
+
+    /// The first point at or after `point_n` with a value other than the
+    /// default. Inserted points holding the default are skipped one by one.
+    pub fn successor_non_default(&self, point_n: N) -> Option<(N, P)> {
+        let default = &self.default;
+        self.root.first_from(&point_n, &|v| v != default).map(Node::point)
+    }
+}
+
+impl<N: Num+Clone+Ord, P: Clone+Ord> PointSegmentTree<N, P> {
+    /// For trees combining with max, the `k` largest inserted points in
+    /// [start_q, end_q] in descending order (equal values from left to
+    /// right).
+    pub fn top_k(&self, start_q: N, end_q: N, k: usize) -> Option<Vec<(N, P)>> {
+        if end_q < start_q || start_q < self.lower_bound || end_q > self.upper_bound {
+            return None;
+        }
+        let mut nodes = Vec::new();
+        self.root.canonical(&start_q, &end_q, &mut nodes);
+        let mut heap: BinaryHeap<_> = nodes.into_iter().map(Candidate).collect();
+        let mut result = Vec::new();
+        while result.len() < k {
+            let node = match heap.pop() {
+                None => break,
+                Some(Candidate(n)) => n
+            };
+            if node.start == node.end {
+                result.push((node.start.clone(), node.value.clone()));
+                continue;
+            }
+            for son in node.left.iter().chain(node.right.iter()) {
+                heap.push(Candidate(son));
+            }
+        }
+        Some(result)
+    }
+
+    /// For trees combining with max, removes the largest inserted point in
+    /// [start_q, end_q] (the leftmost one of equal values) and returns it.
+    /// The point reads as the default value afterwards.
+    pub fn pop_max(&mut self, start_q: N, end_q: N) -> Option<(N, P)> {
+        self.pop_by(start_q, end_q, &|x, y| x > y)
+    }
+
+    /// For trees combining with min, removes the smallest inserted point in
+    /// [start_q, end_q] (the leftmost one of equal values) and returns it.
+    pub fn pop_min(&mut self, start_q: N, end_q: N) -> Option<(N, P)> {
+        self.pop_by(start_q, end_q, &|x, y| x < y)
+    }
+
+    // better(x, y) - whether x should be taken instead of y
+    fn pop_by(&mut self, start_q: N, end_q: N,
+              better: &dyn Fn(&P, &P) -> bool) -> Option<(N, P)> {
+        if end_q < start_q || start_q < self.lower_bound || end_q > self.upper_bound {
+            return None;
+        }
+        // the canonical nodes are siblings of the paths to start_q and end_q,
+        // so the best one is only known after looking at all of them; the
+        // way down to it and then to its best leaf is a single pass
+        let target = {
+            let mut nodes = Vec::new();
+            self.root.canonical(&start_q, &end_q, &mut nodes);
+            let mut best: Option<&Node<N, P>> = None;
+            for n in nodes {
+                if best.is_none_or(|b| better(&n.value, &b.value)) {
+                    best = Some(n);
+                }
+            }
+            best.map(|n| (n.start.clone(), n.end.clone()))
+        };
+        let (start_t, end_t) = target?;
+        let (found, emptied) = self.root.pop_extreme(start_t, end_t, better, &*self.combine);
+        if emptied {
+            self.root = Node::new(self.root.start.clone(), self.root.end.clone(),
+                                  &self.default);
+        }
+        Some(found)
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ScanItem<N, P> {
+    /// An inserted point and the aggregate up to it.
+    Point(N, P),
+    /// Points [start, end] were never inserted; the aggregate stays as is.
+    Unset(N, N, P),
 }
 
+pub struct Scan<'a, N: 'a, P: 'a> {
+    stack: Vec<Pending<'a, N, P>>,
+    // not yet reported stretch of unset points
+    gap: Option<(N, N)>,
+    acc: P,
+    combine: &'a F<P>,
+    start_q: N,
+    end_q: N,
+}
+
+enum Pending<'a, N: 'a, P: 'a> {
+    Node(&'a Node<N, P>),
+    Gap(N, N),
+}
+
+impl<'a, N: Num+Clone+Ord, P: Clone> Iterator for Scan<'a, N, P> {
+    type Item = ScanItem<N, P>;
+
+    fn next(&mut self) -> Option<ScanItem<N, P>> {
+        while let Some(pending) = self.stack.pop() {
+            let node = match pending {
+                Pending::Gap(start, end) => {
+                    self.gap = match self.gap.take() {
+                        None => Some((start, end)),
+                        Some((gap_start, _)) => Some((gap_start, end)),
+                    };
+                    continue;
+                },
+                Pending::Node(n) => n
+            };
+            let start = max(node.start.clone(), self.start_q.clone());
+            let end = min(node.end.clone(), self.end_q.clone());
+            if node.is_empty() {
+                self.stack.push(Pending::Gap(start, end));
+                continue;
+            }
+            if node.start == node.end {
+                if let Some((gap_start, gap_end)) = self.gap.take() {
+                    self.stack.push(Pending::Node(node));
+                    return Some(ScanItem::Unset(gap_start, gap_end, self.acc.clone()));
+                }
+                self.acc = (self.combine)(&self.acc, &node.value);
+                return Some(ScanItem::Point(node.start.clone(), self.acc.clone()));
+            }
+            let mid = mid(node.start.clone(), node.end.clone());
+            if end > mid {
+                self.stack.push(match node.right {
+                    None => Pending::Gap(max(start.clone(), mid.clone() + One::one()), end.clone()),
+                    Some(ref n) => Pending::Node(n)
+                });
+            }
+            if start <= mid {
+                self.stack.push(match node.left {
+                    None => Pending::Gap(start, min(end, mid)),
+                    Some(ref n) => Pending::Node(n)
+                });
+            }
+        }
+        self.gap.take().map(|(start, end)| ScanItem::Unset(start, end, self.acc.clone()))
+    }
+}
+
+pub struct Report<'a, N: 'a, P: 'a, C, M> {
+    stack: Vec<&'a Node<N, P>>,
+    start_q: N,
+    end_q: N,
+    node_may_contain: C,
+    leaf_matches: M,
+}
+
+impl<'a, N, P, C, M> Iterator for Report<'a, N, P, C, M>
+    where N: Num+Clone+Ord, P: Clone, C: FnMut(&P) -> bool, M: FnMut(&P) -> bool
+{
+    type Item = (N, P);
+
+    fn next(&mut self) -> Option<(N, P)> {
+        while let Some(node) = self.stack.pop() {
+            if node.end < self.start_q || node.start > self.end_q || node.is_empty() {
+                continue;
+            }
+            if !(self.node_may_contain)(&node.value) {
+                continue;
+            }
+            if node.start == node.end {
+                if (self.leaf_matches)(&node.value) {
+                    return Some((node.start.clone(), node.value.clone()));
+                }
+                continue;
+            }
+            if let Some(ref n) = node.right {
+                self.stack.push(n);
+            }
+            if let Some(ref n) = node.left {
+                self.stack.push(n);
+            }
+        }
+        None
+    }
+}
+
+// Orders nodes by value, then by position from the left.
+struct Candidate<'a, N: 'a, P: 'a>(&'a Node<N, P>);
+
+impl<'a, N: Ord, P: Ord> Ord for Candidate<'a, N, P> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.value.cmp(&other.0.value)
+            .then_with(|| other.0.start.cmp(&self.0.start))
+    }
+}
+
+impl<'a, N: Ord, P: Ord> PartialOrd for Candidate<'a, N, P> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'a, N: Ord, P: Ord> PartialEq for Candidate<'a, N, P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<'a, N: Ord, P: Ord> Eq for Candidate<'a, N, P> {}
+
 impl<N: Num+Clone+Ord, P: Clone> Node<N, P> {
     fn new(start: N, end: N, default_value: &P) -> Self { 
         Node {
-            start: start,
-            end: end,
+            start,
+            end,
             value: default_value.clone(),
+            inserted: false,
             left: None,
             right: None,
         }
@@ -80,9 +517,54 @@ impl<N: Num+Clone+Ord, P: Clone> Node<N, P> {
         Some(Box::new(Node::new(start, end, default_value)))
     }
 
+    fn is_empty(&self) -> bool {
+        !self.inserted && self.left.is_none() && self.right.is_none()
+    }
+
+    // Maximal nodes with inserted points inside [start_q, end_q], in order.
+    fn canonical<'a>(&'a self, start_q: &N, end_q: &N, acc: &mut Vec<&'a Node<N, P>>) {
+        if *end_q < self.start || *start_q > self.end || self.is_empty() {
+            return;
+        }
+        if *start_q <= self.start && self.end <= *end_q {
+            acc.push(self);
+            return;
+        }
+        if let Some(ref n) = self.left {
+            n.canonical(start_q, end_q, acc);
+        }
+        if let Some(ref n) = self.right {
+            n.canonical(start_q, end_q, acc);
+        }
+    }
+
+    // All canonical nodes of [start_q, end_q], allocated or not, in order.
+    fn views<'a>(&'a self, start_q: &N, end_q: &N, default: &'a P,
+                 acc: &mut Vec<NodeView<'a, N, P>>) {
+        if *end_q < self.start || *start_q > self.end {
+            return;
+        }
+        if *start_q <= self.start && self.end <= *end_q {
+            acc.push(NodeView::new(self as &dyn TreeNode<N, P>));
+            return;
+        }
+        let mid_n = mid(self.start.clone(), self.end.clone());
+        match self.left {
+            Some(ref n) => n.views(start_q, end_q, default, acc),
+            None => unallocated(self.start.clone(), mid_n.clone(), start_q, end_q, default, acc),
+        }
+        match self.right {
+            Some(ref n) => n.views(start_q, end_q, default, acc),
+            None => unallocated(mid_n + One::one(), self.end.clone(), start_q, end_q, default, acc),
+        }
+    }
+
     fn query(&self, start_q: N, end_q: N, combine: &F<P>, acc: P) -> P {
+        if self.is_empty() {
+            return acc;
+        }
         if self.start == start_q && self.end == end_q {
-            return combine(&self.value, &acc);
+            return combine(&acc, &self.value);
         }
         let mid = mid(self.start.clone(), self.end.clone());
 
@@ -102,12 +584,222 @@ impl<N: Num+Clone+Ord, P: Clone> Node<N, P> {
                 None => acc,
                 Some(ref n) => n.query(start_q, mid.clone(), combine, acc)
             };
-            let acc_r = match self.right {
+            match self.right {
                 None => acc_l,
                 Some(ref n) => n.query(mid + One::one(), end_q, combine, acc_l)
-            };
-            acc_r
+            }
+        }
+    }
+
+    fn map_range(&mut self, start_m: &N, end_m: &N,
+                 f: &mut dyn FnMut(&P) -> P, combine: &F<P>) {
+        if *end_m < self.start || *start_m > self.end || self.is_empty() {
+            return;
+        }
+        if self.start == self.end {
+            self.value = f(&self.value);
+            return;
+        }
+        if let Some(n) = self.left.as_mut() {
+            n.map_range(start_m, end_m, f, combine);
+        }
+        if let Some(n) = self.right.as_mut() {
+            n.map_range(start_m, end_m, f, combine);
+        }
+        self.value = match (self.left.as_ref(), self.right.as_ref()) {
+            (Some(l), Some(r)) => combine(&l.value, &r.value),
+            (Some(l), _) => l.value.clone(),
+            (_, Some(r)) => r.value.clone(),
+            _ => unreachable!()
+        };
+    }
+
+    // Leaves points up to k (which is inside this node, but not its end)
+    // here and returns a node of the same range with the points after k.
+    fn split_off(&mut self, k: &N, default: &P, combine: &F<P>) -> Node<N, P> {
+        let mut split = Node::new(self.start.clone(), self.end.clone(), default);
+        let mid = mid(self.start.clone(), self.end.clone());
+        if *k < mid {
+            split.right = self.right.take();
+            split.left = self.left.as_mut()
+                .map(|n| Box::new(n.split_off(k, default, combine)));
+        } else if *k == mid {
+            split.right = self.right.take();
+        } else {
+            split.right = self.right.as_mut()
+                .map(|n| Box::new(n.split_off(k, default, combine)));
+        }
+        self.update(default, combine);
+        split.update(default, combine);
+        split
+    }
+
+    // Drops sons left without points and recomputes the value.
+    fn update(&mut self, default: &P, combine: &F<P>) {
+        if self.left.as_ref().is_some_and(|n| n.is_empty()) {
+            self.left = None;
+        }
+        if self.right.as_ref().is_some_and(|n| n.is_empty()) {
+            self.right = None;
         }
+        self.value = match (self.left.as_ref(), self.right.as_ref()) {
+            (Some(l), Some(r)) => combine(&l.value, &r.value),
+            (Some(l), _) => l.value.clone(),
+            (_, Some(r)) => r.value.clone(),
+            _ => default.clone()
+        };
+    }
+
+    fn merge(&mut self, other: Node<N, P>, merge_leaf: &dyn Fn(&P, &P) -> P,
+             combine: &F<P>) {
+        if other.is_empty() {
+            return;
+        }
+        if self.is_empty() {
+            *self = other;
+            return;
+        }
+        if self.start == self.end {
+            self.value = merge_leaf(&self.value, &other.value);
+            return;
+        }
+        self.left = Node::merge_sons(self.left.take(), other.left, merge_leaf, combine);
+        self.right = Node::merge_sons(self.right.take(), other.right, merge_leaf, combine);
+        self.value = match (self.left.as_ref(), self.right.as_ref()) {
+            (Some(l), Some(r)) => combine(&l.value, &r.value),
+            (Some(l), _) => l.value.clone(),
+            (_, Some(r)) => r.value.clone(),
+            _ => unreachable!()
+        };
+    }
+
+    fn merge_sons(son: Option<Box<Node<N, P>>>, other: Option<Box<Node<N, P>>>,
+                  merge_leaf: &dyn Fn(&P, &P) -> P, combine: &F<P>) -> Option<Box<Node<N, P>>> {
+        match (son, other) {
+            (Some(mut n), Some(o)) => {
+                n.merge(*o, merge_leaf, combine);
+                Some(n)
+            },
+            (n, None) => n,
+            (None, o) => o,
+        }
+    }
+
+    // ranges - sorted, disjoint, not touching and within this node
+    fn query_ranges(&self, ranges: &[(N, N)], combine: &F<P>, acc: P) -> P {
+        if ranges.is_empty() || self.is_empty() {
+            return acc;
+        }
+        if ranges.len() == 1 && ranges[0].0 == self.start && ranges[0].1 == self.end {
+            return combine(&acc, &self.value);
+        }
+        let mid = mid(self.start.clone(), self.end.clone());
+        let mut left = Vec::new();
+        let mut right = Vec::new();
+        for (start_q, end_q) in ranges {
+            if *start_q <= mid {
+                left.push((start_q.clone(), min(end_q.clone(), mid.clone())));
+            }
+            if *end_q > mid {
+                right.push((max(start_q.clone(), mid.clone() + One::one()), end_q.clone()));
+            }
+        }
+        let acc_l = match self.left {
+            None => acc,
+            Some(ref n) => n.query_ranges(&left, combine, acc)
+        };
+        match self.right {
+            None => acc_l,
+            Some(ref n) => n.query_ranges(&right, combine, acc_l)
+        }
+    }
+
+    // The leaf holding `value`, which must be the value of some leaf below.
+    fn position_of(&self, value: &P, tie_break: TieBreak) -> (N, P)
+        where P: PartialEq
+    {
+        let holds = |son: &Option<Box<Node<N, P>>>| {
+            son.as_ref().is_some_and(|n| n.value == *value)
+        };
+        let son = match tie_break {
+            TieBreak::Leftmost if holds(&self.left) => &self.left,
+            TieBreak::Leftmost => &self.right,
+            TieBreak::Rightmost if holds(&self.right) => &self.right,
+            TieBreak::Rightmost => &self.left,
+        };
+        match son.as_ref() {
+            None => (self.start.clone(), self.value.clone()),
+            Some(n) => n.position_of(value, tie_break)
+        }
+    }
+
+    fn point(&self) -> (N, P) {
+        (self.start.clone(), self.value.clone())
+    }
+
+    // The leftmost leaf at or after point_n whose value is accepted.
+    fn first_from(&self, point_n: &N, accept: &dyn Fn(&P) -> bool) -> Option<&Node<N, P>> {
+        if self.end < *point_n || self.is_empty() {
+            return None;
+        }
+        if self.start == self.end {
+            return if accept(&self.value) { Some(self) } else { None };
+        }
+        self.left.as_ref().and_then(|n| n.first_from(point_n, accept))
+            .or_else(|| self.right.as_ref().and_then(|n| n.first_from(point_n, accept)))
+    }
+
+    // The rightmost leaf at or before point_n whose value is accepted.
+    fn last_until(&self, point_n: &N, accept: &dyn Fn(&P) -> bool) -> Option<&Node<N, P>> {
+        if self.start > *point_n || self.is_empty() {
+            return None;
+        }
+        if self.start == self.end {
+            return if accept(&self.value) { Some(self) } else { None };
+        }
+        self.right.as_ref().and_then(|n| n.last_until(point_n, accept))
+            .or_else(|| self.left.as_ref().and_then(|n| n.last_until(point_n, accept)))
+    }
+
+    // Removes the best leaf below the node [start_t, end_t], which must
+    // have points; returns it and whether this node has no points left.
+    fn pop_extreme(&mut self, start_t: N, end_t: N, better: &dyn Fn(&P, &P) -> bool,
+                   combine: &F<P>) -> ((N, P), bool) {
+        if self.start == self.end {
+            return (self.point(), true);
+        }
+        let at_target = self.start == start_t && self.end == end_t;
+        let go_right = if at_target {
+            match (self.left.as_ref(), self.right.as_ref()) {
+                (Some(l), Some(r)) => better(&r.value, &l.value),
+                (l, _) => l.is_none(),
+            }
+        } else {
+            start_t > mid(self.start.clone(), self.end.clone())
+        };
+        let son = if go_right { &mut self.right } else { &mut self.left };
+        let (found, emptied) = match son.as_mut() {
+            Some(n) => {
+                // below the target, follow the best son all the way down
+                let (start_n, end_n) = if at_target {
+                    (n.start.clone(), n.end.clone())
+                } else {
+                    (start_t, end_t)
+                };
+                n.pop_extreme(start_n, end_n, better, combine)
+            },
+            None => unreachable!()
+        };
+        if emptied {
+            *son = None;
+        }
+        match (self.left.as_ref(), self.right.as_ref()) {
+            (Some(l), Some(r)) => self.value = combine(&l.value, &r.value),
+            (Some(l), _) => self.value = l.value.clone(),
+            (_, Some(r)) => self.value = r.value.clone(),
+            _ => return (found, true)
+        }
+        (found, false)
     }
 
     fn insert(&mut self, point_n: N, point_data: P,
@@ -115,23 +807,40 @@ impl<N: Num+Clone+Ord, P: Clone> Node<N, P> {
         let mid = mid(self.start.clone(), self.end.clone());
         if self.start == self.end {
             self.value = point_data;
+            self.inserted = true;
             return;
         } else if point_n <= mid {
             if self.left.is_none() {
                 self.left = Node::new_son(self.start.clone(), mid, default);
             }
-            self.left.as_mut().map(|n| n.insert(point_n, point_data, default, combine));
+            if let Some(n) = self.left.as_mut() {
+                n.insert(point_n, point_data, default, combine);
+            }
         } else { // point_n > mid
             if self.right.is_none() {
                 self.right = Node::new_son(mid + One::one(), self.end.clone(), default);
             }
-            self.right.as_mut().map(|n| n.insert(point_n, point_data, default, combine));
+            if let Some(n) = self.right.as_mut() {
+                n.insert(point_n, point_data, default, combine);
+            }
         }
         self.value = match (self.left.as_ref(), self.right.as_ref()) {
-            (Some(ref l), Some(ref r)) => combine(&l.value, &r.value),
-            (Some(ref l), _) => l.value.clone(),
-            (_, Some(ref r)) => r.value.clone(),
+            (Some(l), Some(r)) => combine(&l.value, &r.value),
+            (Some(l), _) => l.value.clone(),
+            (_, Some(r)) => r.value.clone(),
             _ => unreachable!()
         };
     }
 }
+
+impl<N, P> TreeNode<N, P> for Node<N, P> {
+    fn start(&self) -> &N { &self.start }
+    fn end(&self) -> &N { &self.end }
+    fn value(&self) -> &P { &self.value }
+    fn left(&self) -> Option<&dyn TreeNode<N, P>> {
+        self.left.as_ref().map(|n| &**n as &dyn TreeNode<N, P>)
+    }
+    fn right(&self) -> Option<&dyn TreeNode<N, P>> {
+        self.right.as_ref().map(|n| &**n as &dyn TreeNode<N, P>)
+    }
+}
@@ -2,30 +2,58 @@ extern crate num;
 
 use self::num::traits::{Num};
 use self::num::traits::{One};
-use common::{mid};
+use common::{mid, unallocated};
+use std::cmp::{max, min};
+use std::mem;
+use std::rc::{Rc};
+use view::{NodeView, TreeNode};
 
 pub struct SegmentPointTree<N, S>{
     root: Node<N, S>,
-    lower_bound: N, 
-    upper_bound: N, 
+    lower_bound: N,
+    upper_bound: N,
     default: S,
-    combine: Box<F<S>>
+    // shared with the trees split off from this one
+    combine: Rc<F<S>>,
+    ordered: bool,
+    next_seq: usize,
 }
 
 #[derive(PartialEq, Eq, Debug)]
 struct Node<N, S> {
     start: N,
     end: N,
-    value: S, 
+    value: S,
+    // (insertion sequence, segment) pairs, only used by ordered trees;
+    // kept sorted by sequence since inserts only ever append
+    stamped: Vec<(usize, S)>,
     left: Option<Box<Node<N, S>>>,
     right: Option<Box<Node<N, S>>>,
 }
 
-pub type F<S> = Fn(&S, &S) -> S;
+pub type F<S> = dyn Fn(&S, &S) -> S;
 
 impl<N: Num+Clone+Ord, S: Clone> SegmentPointTree<N, S> {
     pub fn new(lower_bound: N, upper_bound: N, default_value: S,
-               combine: Box<F<S>>) -> Self 
+               combine: Box<F<S>>) -> Self
+    {
+        SegmentPointTree::with_mode(lower_bound, upper_bound, default_value,
+                                    combine, false)
+    }
+
+    /// Creates a tree which remembers the order of inserts. `query` folds
+    /// all segments containing the point with `combine` in insertion order
+    /// (oldest first), regardless of where in the tree they are stored, so
+    /// non-commutative operations (like "latest assignment wins") work.
+    pub fn new_ordered(lower_bound: N, upper_bound: N, default_value: S,
+                       combine: Box<F<S>>) -> Self
+    {
+        SegmentPointTree::with_mode(lower_bound, upper_bound, default_value,
+                                    combine, true)
+    }
+
+    fn with_mode(lower_bound: N, upper_bound: N, default_value: S,
+                 combine: Box<F<S>>, ordered: bool) -> Self
     {
         if upper_bound < lower_bound {
             panic!("Invalid bounds (lower_bound must not be greater than upper_bound)");
@@ -35,7 +63,9 @@ impl<N: Num+Clone+Ord, S: Clone> SegmentPointTree<N, S> {
             upper_bound: upper_bound.clone(),
             default: default_value.clone(),
             root: Node::new(lower_bound, upper_bound, &default_value),
-            combine: combine
+            combine: Rc::from(combine),
+            ordered,
+            next_seq: 0,
         }
     }
 
@@ -43,20 +73,146 @@ impl<N: Num+Clone+Ord, S: Clone> SegmentPointTree<N, S> {
         let s = self.default.clone();
         if point > self.upper_bound || point < self.lower_bound {
             None
+        } else if self.ordered {
+            let mut stamps = Vec::new();
+            self.root.collect_stamped(point, &mut stamps);
+            stamps.sort_by_key(|stamp| stamp.0);
+            Some(stamps.iter().fold(s, |acc, stamp| (self.combine)(&acc, &stamp.1)))
         } else {
             Some(self.root.query(point, &*self.combine, s))
         }
     }
 
     pub fn bounds(&self) -> (N, N) {
-        return (self.lower_bound.clone(), self.upper_bound.clone())
+        (self.lower_bound.clone(), self.upper_bound.clone())
+    }
+
+    pub fn root(&self) -> NodeView<'_, N, S> {
+        NodeView::new(&self.root)
+    }
+
+    /// The nodes an insert of [start, end] would add its value to, in
+    /// order. Those the tree doesn't have yet are reported as unallocated,
+    /// holding the default. In ordered trees the inserted values are kept
+    /// aside, so node values stay at the default.
+    pub fn decompose(&self, start: N, end: N) -> Option<Vec<NodeView<'_, N, S>>> {
+        if end < start || start < self.lower_bound || end > self.upper_bound {
+            return None;
+        }
+        let mut nodes = Vec::new();
+        self.root.canonical(&start, &end, &self.default, &mut nodes);
+        Some(nodes)
     }
 
-    pub fn insert(&mut self, start: N, end: N, segment: S) -> () {
+    /// Adds the segments of `other`, which must have the same bounds and
+    /// mode (and, for trees made by `split_off`, be split off the same
+    /// way), node by node. In ordered trees the segments of `other` count
+    /// as inserted after all segments of `self`, keeping their own order.
+    pub fn merge(mut self, other: Self) -> Self {
+        if self.bounds() != other.bounds()
+            || self.root.start != other.root.start || self.root.end != other.root.end {
+            panic!("Can't merge trees with different bounds");
+        }
+        if self.ordered != other.ordered {
+            panic!("Can't merge ordered and unordered trees");
+        }
+        self.root.merge(other.root, self.next_seq, &*self.combine);
+        self.next_seq += other.next_seq;
+        self
+    }
+
+    /// Moves [k + 1, upper_bound] to a new tree, leaving [lower_bound, k]
+    /// in this one. Nodes on the path to `k` cover points on both sides, so
+    /// they are copied; all other nodes are moved. Both trees keep the node
+    /// layout of the original one.
+    pub fn split_off(&mut self, k: N) -> Self {
+        if k < self.lower_bound || k >= self.upper_bound {
+            panic!("Can't split outside of bounds");
+        }
+        let root = self.root.split_off(&k, &self.default);
+        let upper_bound = mem::replace(&mut self.upper_bound, k.clone());
+        SegmentPointTree {
+            root,
+            lower_bound: k + One::one(),
+            upper_bound,
+            default: self.default.clone(),
+            combine: self.combine.clone(),
+            ordered: self.ordered,
+            next_seq: self.next_seq,
+        }
+    }
+
+    /// The values of all points in [start, end], in order.
+    pub fn to_dense_vec(&self, start: N, end: N) -> Option<Vec<S>> {
+        if end < start || start < self.lower_bound || end > self.upper_bound {
+            return None;
+        }
+        let mut values = Vec::new();
+        self.for_each_piece(&start, &end, &mut |start_p, end_p, value| {
+            let mut point = start_p;
+            loop {
+                values.push(value.clone());
+                if point == end_p {
+                    break;
+                }
+                point = point + One::one();
+            }
+        });
+        Some(values)
+    }
+
+    // Calls f on consecutive pieces of [start, end] on which no inserted
+    // segment begins or ends, with the value of their points. Every piece
+    // is found in the same walk, carrying what was collected above it.
+    fn for_each_piece(&self, start: &N, end: &N, f: &mut dyn FnMut(N, N, S)) {
+        let mut stamps = Vec::new();
+        self.root.pieces(start, end, &self.default, &mut stamps, &*self.combine,
+                         &mut |start_p, end_p, acc, stamps| {
+            let value = if self.ordered {
+                let mut stamps = stamps.to_vec();
+                stamps.sort_by_key(|stamp| stamp.0);
+                stamps.iter().fold(self.default.clone(),
+                                   |acc, stamp| (self.combine)(&acc, &stamp.1))
+            } else {
+                acc.clone()
+            };
+            f(start_p, end_p, value);
+        });
+    }
+
+    pub fn is_ordered(&self) -> bool {
+        self.ordered
+    }
+
+    pub fn insert(&mut self, start: N, end: N, segment: S) {
         if start < self.lower_bound || end > self.upper_bound {
             panic!("Can't insert outside of bounds");
         }
-        self.root.insert(start, end, &segment, &self.default, &*self.combine);
+        let stamp = if self.ordered {
+            self.next_seq += 1;
+            Some(self.next_seq - 1)
+        } else {
+            None
+        };
+        self.root.insert(start, end, &segment, stamp, &self.default, &*self.combine);
+    }
+}
+
+impl<N: Num+Clone+Ord, S: Clone+PartialEq> SegmentPointTree<N, S> {
+    /// Splits [start, end] into maximal runs of points with equal values,
+    /// as (start, end, value) triples in order.
+    pub fn to_runs(&self, start: N, end: N) -> Option<Vec<(N, N, S)>> {
+        if end < start || start < self.lower_bound || end > self.upper_bound {
+            return None;
+        }
+        let mut runs: Vec<(N, N, S)> = Vec::new();
+        self.for_each_piece(&start, &end, &mut |start_p, end_p, value| {
+            match runs.last_mut() {
+                Some(run) if run.2 == value => run.1 = end_p,
+                _ => runs.push((start_p, end_p, value)),
+            }
+        });
+        Some(runs)
     }
 }
 
@@ -64,36 +220,154 @@ impl<N: Num+Clone+Ord, S: Clone> Node<N, S> {
     fn new(start: N, end: N, default_value: &S) -> Self {
         Node {
             value: default_value.clone(),
-            start: start,
-            end: end,
+            stamped: Vec::new(),
+            start,
+            end,
             left: None,
             right: None,
         }
     }
 
     fn new_son(start: N, end: N, default_value: &S) -> Option<Box<Self>> {
-        Some(Box::new(Node::new(start, end, &default_value)))
+        Some(Box::new(Node::new(start, end, default_value)))
     }
 
-    fn query(&self, point: N, combine: &F<S>, acc: S) -> S { 
+    fn query(&self, point: N, combine: &F<S>, acc: S) -> S {
         let acc2 = combine(&acc, &self.value);
         let mid_n = mid(self.start.clone(), self.end.clone());
-        let ref son = if point <= mid_n {
+        let son = if point <= mid_n {
             &self.left
         } else {
             &self.right
         };
         match son.as_ref() {
-            None => acc2,   
+            None => acc2,
             Some(n) => n.query(point, combine, acc2)
         }// .map_or(acc2, |n| n.query(point, combine, acc2))
     }
 
-    fn insert(&mut self, start_s: N, end_s: N, value_s: &S, 
-              default: &S, combine: &F<S>) -> () {
+    fn canonical<'a>(&'a self, start_s: &N, end_s: &N, default: &'a S,
+                     acc: &mut Vec<NodeView<'a, N, S>>) {
+        if *end_s < self.start || *start_s > self.end {
+            return;
+        }
+        if *start_s <= self.start && self.end <= *end_s {
+            acc.push(NodeView::new(self as &dyn TreeNode<N, S>));
+            return;
+        }
+        let mid_n = mid(self.start.clone(), self.end.clone());
+        match self.left {
+            Some(ref n) => n.canonical(start_s, end_s, default, acc),
+            None => unallocated(self.start.clone(), mid_n.clone(), start_s, end_s, default, acc),
+        }
+        match self.right {
+            Some(ref n) => n.canonical(start_s, end_s, default, acc),
+            None => unallocated(mid_n + One::one(), self.end.clone(), start_s, end_s, default, acc),
+        }
+    }
+
+    fn pieces<'a, G>(&'a self, start_q: &N, end_q: &N, acc: &S,
+                     stamps: &mut Vec<&'a (usize, S)>, combine: &F<S>, f: &mut G)
+        where G: FnMut(N, N, &S, &[&'a (usize, S)])
+    {
+        if *end_q < self.start || *start_q > self.end {
+            return;
+        }
+        let acc2 = combine(acc, &self.value);
+        let pushed = stamps.len();
+        stamps.extend(self.stamped.iter());
+        let mid_n = mid(self.start.clone(), self.end.clone());
+        match self.left.as_ref() {
+            Some(n) => n.pieces(start_q, end_q, &acc2, stamps, combine, f),
+            None => if *start_q <= mid_n {
+                f(max(self.start.clone(), start_q.clone()), min(mid_n.clone(), end_q.clone()),
+                  &acc2, stamps);
+            },
+        }
+        if mid_n < self.end {
+            match self.right.as_ref() {
+                Some(n) => n.pieces(start_q, end_q, &acc2, stamps, combine, f),
+                None => if *end_q > mid_n {
+                    f(max(mid_n + One::one(), start_q.clone()), min(self.end.clone(), end_q.clone()),
+                      &acc2, stamps);
+                },
+            }
+        }
+        stamps.truncate(pushed);
+    }
+
+    // k is inside this node, but not its end.
+    fn split_off(&mut self, k: &N, default: &S) -> Node<N, S> {
+        let mut split = Node::new(self.start.clone(), self.end.clone(), default);
+        split.value = self.value.clone();
+        split.stamped = self.stamped.clone();
+        let mid_n = mid(self.start.clone(), self.end.clone());
+        if *k < mid_n {
+            split.right = self.right.take();
+            split.left = self.left.as_mut().map(|n| Box::new(n.split_off(k, default)));
+        } else if *k == mid_n {
+            split.right = self.right.take();
+        } else {
+            split.right = self.right.as_mut().map(|n| Box::new(n.split_off(k, default)));
+        }
+        split
+    }
+
+    fn merge(&mut self, other: Node<N, S>, offset: usize, combine: &F<S>) {
+        self.value = combine(&self.value, &other.value);
+        self.stamped.extend(other.stamped.into_iter().map(|(seq, s)| (seq + offset, s)));
+        self.left = Node::merge_sons(self.left.take(), other.left, offset, combine);
+        self.right = Node::merge_sons(self.right.take(), other.right, offset, combine);
+    }
+
+    fn merge_sons(son: Option<Box<Node<N, S>>>, other: Option<Box<Node<N, S>>>,
+                  offset: usize, combine: &F<S>) -> Option<Box<Node<N, S>>> {
+        match (son, other) {
+            (Some(mut n), Some(o)) => {
+                n.merge(*o, offset, combine);
+                Some(n)
+            },
+            (n, None) => n,
+            (None, Some(mut o)) => {
+                o.shift_stamps(offset);
+                Some(o)
+            },
+        }
+    }
+
+    fn shift_stamps(&mut self, offset: usize) {
+        for stamp in self.stamped.iter_mut() {
+            stamp.0 += offset;
+        }
+        if let Some(n) = self.left.as_mut() {
+            n.shift_stamps(offset);
+        }
+        if let Some(n) = self.right.as_mut() {
+            n.shift_stamps(offset);
+        }
+    }
+
+    fn collect_stamped<'a>(&'a self, point: N, acc: &mut Vec<&'a (usize, S)>) {
+        acc.extend(self.stamped.iter());
+        let mid_n = mid(self.start.clone(), self.end.clone());
+        let son = if point <= mid_n {
+            &self.left
+        } else {
+            &self.right
+        };
+        if let Some(n) = son.as_ref() {
+            n.collect_stamped(point, acc);
+        }
+    }
+
+    fn insert(&mut self, start_s: N, end_s: N, value_s: &S, stamp: Option<usize>,
+              default: &S, combine: &F<S>) {
         if start_s == self.start && end_s == self.end {
             // ugh.
-            self.value = combine(&self.value, value_s);
+            match stamp {
+                Some(seq) => self.stamped.push((seq, value_s.clone())),
+                None => self.value = combine(&self.value, value_s),
+            }
             return;
         }
         let mid_n = mid(self.start.clone(), self.end.clone());
@@ -102,28 +376,43 @@ impl<N: Num+Clone+Ord, S: Clone> Node<N, S> {
             if self.left.is_none() {
                 self.left = Node::new_son(self.start.clone(), mid_n, default);
             }
-            self.left.as_mut().map(|n| n.insert(start_s, end_s, 
-                                                value_s, default, combine));
+            if let Some(n) = self.left.as_mut() {
+                n.insert(start_s, end_s, value_s, stamp, default, combine);
+            }
         } else if start_s > mid_n {
             // only right
             if self.right.is_none() {
                 self.right = Node::new_son(mid_n + One::one(), self.end.clone(), default);
             }
-            self.right.as_mut().map(|n| n.insert(start_s, end_s, 
-                                                 value_s, default, combine));
+            if let Some(n) = self.right.as_mut() {
+                n.insert(start_s, end_s, value_s, stamp, default, combine);
+            }
         } else {
             // both
             if self.left.is_none() {
                 self.left = Node::new_son(self.start.clone(), mid_n.clone(), default);
             }
-            self.left.as_mut().map(|n| n.insert(start_s, mid_n.clone(), 
-                                                value_s, default, combine));
+            if let Some(n) = self.left.as_mut() {
+                n.insert(start_s, mid_n.clone(), value_s, stamp, default, combine);
+            }
             if self.right.is_none() {
                 self.right = Node::new_son(mid_n.clone() + One::one(), self.end.clone(), default);
             }
-            self.right.as_mut().map(|n| n.insert(mid_n + One::one(), end_s, 
-                                                 value_s, default, combine));
+            if let Some(n) = self.right.as_mut() {
+                n.insert(mid_n + One::one(), end_s, value_s, stamp, default, combine);
+            }
         }
     }
 }
 
+impl<N, S> TreeNode<N, S> for Node<N, S> {
+    fn start(&self) -> &N { &self.start }
+    fn end(&self) -> &N { &self.end }
+    fn value(&self) -> &S { &self.value }
+    fn left(&self) -> Option<&dyn TreeNode<N, S>> {
+        self.left.as_ref().map(|n| &**n as &dyn TreeNode<N, S>)
+    }
+    fn right(&self) -> Option<&dyn TreeNode<N, S>> {
+        self.right.as_ref().map(|n| &**n as &dyn TreeNode<N, S>)
+    }
+}
@@ -0,0 +1,229 @@
+//! Mergeable sketches giving approximate answers over huge ranges, usable as
+//! values of a PointSegmentTree:
+//!
+//!   PointSegmentTree::new(0, n, Kll::identity(), Box::new(Kll::combine))
+//!   PointSegmentTree::new(0, n, HyperLogLog::identity(), Box::new(HyperLogLog::combine))
+//!
+//! identity() is an empty sketch which takes its parameters from whatever
+//! it is combined with, so it can serve as the tree default.
+
+use std::cmp::{max, min};
+use std::collections::hash_map::{DefaultHasher};
+use std::hash::{Hash, Hasher};
+use std::mem;
+
+/// Approximate count of distinct values. Relative error is about
+/// 1.04 / sqrt(2^precision).
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct HyperLogLog {
+    precision: u8,
+    // empty for the identity
+    registers: Vec<u8>,
+}
+
+impl HyperLogLog {
+    pub fn identity() -> Self {
+        HyperLogLog { precision: 0, registers: Vec::new() }
+    }
+
+    /// An empty sketch with 2^precision registers, precision in [4, 16].
+    pub fn new(precision: u8) -> Self {
+        if !(4..=16).contains(&precision) {
+            panic!("Invalid precision (must be between 4 and 16)");
+        }
+        HyperLogLog { precision, registers: vec![0; 1 << precision] }
+    }
+
+    pub fn of<T: Hash + ?Sized>(precision: u8, value: &T) -> Self {
+        let mut sketch = HyperLogLog::new(precision);
+        sketch.insert(value);
+        sketch
+    }
+
+    pub fn insert<T: Hash + ?Sized>(&mut self, value: &T) {
+        if self.registers.is_empty() {
+            panic!("Can't insert into the identity sketch");
+        }
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        let hash = hasher.finish();
+        let index = (hash >> (64 - self.precision)) as usize;
+        let rest = hash << self.precision;
+        let rank = min(rest.leading_zeros() as u8 + 1, 64 - self.precision + 1);
+        self.registers[index] = max(self.registers[index], rank);
+    }
+
+    pub fn combine(x: &Self, y: &Self) -> Self {
+        if x.registers.is_empty() {
+            return y.clone();
+        }
+        if y.registers.is_empty() {
+            return x.clone();
+        }
+        if x.precision != y.precision {
+            panic!("Can't combine sketches of different precision");
+        }
+        HyperLogLog {
+            precision: x.precision,
+            registers: x.registers.iter().zip(y.registers.iter())
+                .map(|(a, b)| max(*a, *b))
+                .collect(),
+        }
+    }
+
+    /// Estimated number of distinct inserted values.
+    pub fn estimate(&self) -> f64 {
+        if self.registers.is_empty() {
+            return 0.;
+        }
+        let m = self.registers.len() as f64;
+        let alpha = match self.registers.len() {
+            16 => 0.673,
+            32 => 0.697,
+            64 => 0.709,
+            _ => 0.7213 / (1. + 1.079 / m),
+        };
+        let sum: f64 = self.registers.iter().map(|r| (-(*r as f64)).exp2()).sum();
+        let raw = alpha * m * m / sum;
+        let zeros = self.registers.iter().filter(|r| **r == 0).count();
+        if raw <= 2.5 * m && zeros > 0 {
+            // linear counting is more accurate for small cardinalities
+            m * (m / zeros as f64).ln()
+        } else {
+            raw
+        }
+    }
+}
+
+/// KLL quantile sketch. Rank queries are off by about 1.7 / k of the
+/// number of values, while only O(k) values are kept.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Kll {
+    // 0 for the identity
+    k: usize,
+    // values at level h stand for 2^h original values each
+    levels: Vec<Vec<f64>>,
+    // decides which half survives a compaction; deterministic, so equal
+    // inputs always give equal sketches
+    coin: u64,
+}
+
+impl Kll {
+    pub fn identity() -> Self {
+        Kll { k: 0, levels: Vec::new(), coin: 0 }
+    }
+
+    /// An empty sketch with accuracy parameter k (at least 8).
+    pub fn new(k: usize) -> Self {
+        if k < 8 {
+            panic!("Invalid k (must be at least 8)");
+        }
+        Kll { k, levels: vec![Vec::new()], coin: 0 }
+    }
+
+    pub fn of(k: usize, value: f64) -> Self {
+        let mut sketch = Kll::new(k);
+        sketch.insert(value);
+        sketch
+    }
+
+    pub fn insert(&mut self, value: f64) {
+        if self.k == 0 {
+            panic!("Can't insert into the identity sketch");
+        }
+        if value.is_nan() {
+            panic!("Can't insert NaN into a sketch");
+        }
+        self.levels[0].push(value);
+        self.compress();
+    }
+
+    pub fn combine(x: &Self, y: &Self) -> Self {
+        if x.k == 0 {
+            return y.clone();
+        }
+        if y.k == 0 {
+            return x.clone();
+        }
+        if x.k != y.k {
+            panic!("Can't combine sketches with different k");
+        }
+        let mut result = x.clone();
+        result.coin ^= y.coin.rotate_left(17);
+        for (h, level) in y.levels.iter().enumerate() {
+            if h == result.levels.len() {
+                result.levels.push(Vec::new());
+            }
+            result.levels[h].extend(level.iter().cloned());
+        }
+        result.compress();
+        result
+    }
+
+    /// Number of values summarized by the sketch.
+    pub fn count(&self) -> u64 {
+        self.levels.iter().enumerate()
+            .map(|(h, level)| (level.len() as u64) << h)
+            .sum()
+    }
+
+    /// Approximate number of values not greater than `value`.
+    pub fn rank(&self, value: f64) -> u64 {
+        self.levels.iter().enumerate()
+            .map(|(h, level)| (level.iter().filter(|v| **v <= value).count() as u64) << h)
+            .sum()
+    }
+
+    /// Approximate q-quantile, q in [0, 1]; None for an empty sketch.
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        if !(0. ..=1.).contains(&q) {
+            panic!("Invalid quantile (must be between 0 and 1)");
+        }
+        let mut weighted: Vec<(f64, u64)> = self.levels.iter().enumerate()
+            .flat_map(|(h, level)| level.iter().map(move |v| (*v, 1u64 << h)))
+            .collect();
+        weighted.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let total = self.count();
+        let target = (q * total as f64).ceil().max(1.) as u64;
+        let mut seen = 0;
+        for (value, weight) in weighted {
+            seen += weight;
+            if seen >= target {
+                return Some(value);
+            }
+        }
+        None
+    }
+
+    fn capacity(&self, level: usize) -> usize {
+        let depth = self.levels.len() - 1 - level;
+        max(2, (self.k as f64 * (2f64 / 3.).powi(depth as i32)).ceil() as usize)
+    }
+
+    fn compress(&mut self) {
+        loop {
+            let total: usize = self.levels.iter().map(Vec::len).sum();
+            let capacity: usize = (0..self.levels.len()).map(|h| self.capacity(h)).sum();
+            if total <= capacity {
+                return;
+            }
+            let h = (0..self.levels.len())
+                .find(|&h| self.levels[h].len() >= self.capacity(h))
+                .unwrap();
+            if h + 1 == self.levels.len() {
+                self.levels.push(Vec::new());
+            }
+            self.coin = self.coin.wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            let offset = (self.coin >> 63) as usize;
+            let mut level = mem::take(&mut self.levels[h]);
+            level.sort_by(f64::total_cmp);
+            if level.len() % 2 == 1 {
+                // an odd value out stays where it was
+                self.levels[h].push(level.pop().unwrap());
+            }
+            let promoted = level.into_iter().skip(offset).step_by(2);
+            self.levels[h + 1].extend(promoted);
+        }
+    }
+}
@@ -0,0 +1,90 @@
+//! Descriptive statistics which can be merged, so they work as values of
+//! both trees:
+//!
+//!   PointSegmentTree::new(0, n, Stats::identity(), Box::new(Stats::combine))
+//!   SegmentPointTree::new(0, n, Stats::identity(), Box::new(Stats::combine))
+//!
+//! Merging uses the parallel variance formula (Chan et al.), which stays
+//! numerically stable when combining many partial results.
+
+use std::f64;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Stats {
+    count: u64,
+    mean: f64,
+    // sum of squared differences from the mean
+    m2: f64,
+    min: f64,
+    max: f64,
+}
+
+impl Stats {
+    pub fn identity() -> Self {
+        Stats { count: 0, mean: 0., m2: 0., min: f64::INFINITY, max: f64::NEG_INFINITY }
+    }
+
+    pub fn of(value: f64) -> Self {
+        Stats { count: 1, mean: value, m2: 0., min: value, max: value }
+    }
+
+    pub fn combine(x: &Self, y: &Self) -> Self {
+        if x.count == 0 {
+            return *y;
+        }
+        if y.count == 0 {
+            return *x;
+        }
+        let count = x.count + y.count;
+        let delta = y.mean - x.mean;
+        let weight = y.count as f64 / count as f64;
+        Stats {
+            count,
+            mean: x.mean + delta * weight,
+            m2: x.m2 + y.m2 + delta * delta * x.count as f64 * weight,
+            min: x.min.min(y.min),
+            max: x.max.max(y.max),
+        }
+    }
+
+    /// Adds a single value.
+    pub fn push(&mut self, value: f64) {
+        *self = Stats::combine(self, &Stats::of(value));
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn sum(&self) -> f64 {
+        self.mean * self.count as f64
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        if self.count == 0 { None } else { Some(self.mean) }
+    }
+
+    /// Population variance.
+    pub fn variance(&self) -> Option<f64> {
+        if self.count == 0 { None } else { Some(self.m2 / self.count as f64) }
+    }
+
+    /// Sample variance (with Bessel's correction), None for fewer than two
+    /// values.
+    pub fn sample_variance(&self) -> Option<f64> {
+        if self.count < 2 { None } else { Some(self.m2 / (self.count - 1) as f64) }
+    }
+
+    /// Population standard deviation.
+    pub fn std_dev(&self) -> Option<f64> {
+        self.variance().map(f64::sqrt)
+    }
+
+    pub fn min(&self) -> Option<f64> {
+        if self.count == 0 { None } else { Some(self.min) }
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        if self.count == 0 { None } else { Some(self.max) }
+    }
+}
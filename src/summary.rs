@@ -0,0 +1,245 @@
+//! Ready-made values for PointSegmentTree, together with their (not
+//! commutative) combine. Use identity() as the tree default and combine as
+//! the tree operation, and insert of(x) for each point:
+//!
+//!   PointSegmentTree::new(0, 100, MaxSubarray::identity(),
+//!                         Box::new(MaxSubarray::combine))
+//!
+//! Points which were never inserted are identities. MaxSubarray skips them,
+//! summing over the inserted points in the order of their keys; EqualRun and
+//! IncreasingRun are given the key of each point and end a run wherever a key
+//! is missing.
+
+extern crate num;
+
+use self::num::traits::{Num};
+use std::cmp::{max};
+
+fn larger<T: PartialOrd>(x: T, y: T) -> T {
+    if y > x { y } else { x }
+}
+
+/// Maximum sum of a non-empty contiguous run of points.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct MaxSubarray<T> {
+    total: T,
+    prefix: T,
+    suffix: T,
+    best: T,
+    len: usize,
+}
+
+impl<T: Num+Clone+PartialOrd> MaxSubarray<T> {
+    pub fn identity() -> Self {
+        MaxSubarray {
+            total: T::zero(),
+            prefix: T::zero(),
+            suffix: T::zero(),
+            best: T::zero(),
+            len: 0,
+        }
+    }
+
+    pub fn of(value: T) -> Self {
+        MaxSubarray {
+            total: value.clone(),
+            prefix: value.clone(),
+            suffix: value.clone(),
+            best: value,
+            len: 1,
+        }
+    }
+
+    /// `x` must be the summary of the points directly before the points of `y`.
+    pub fn combine(x: &Self, y: &Self) -> Self {
+        if x.len == 0 {
+            return y.clone();
+        }
+        if y.len == 0 {
+            return x.clone();
+        }
+        MaxSubarray {
+            total: x.total.clone() + y.total.clone(),
+            prefix: larger(x.prefix.clone(), x.total.clone() + y.prefix.clone()),
+            suffix: larger(y.suffix.clone(), x.suffix.clone() + y.total.clone()),
+            best: larger(larger(x.best.clone(), y.best.clone()),
+                         x.suffix.clone() + y.prefix.clone()),
+            len: x.len + y.len,
+        }
+    }
+
+    /// The largest sum of consecutive points, None if there are no points.
+    pub fn best(&self) -> Option<T> {
+        if self.len == 0 { None } else { Some(self.best.clone()) }
+    }
+
+    /// The largest sum of a run starting at the first point.
+    pub fn prefix(&self) -> Option<T> {
+        if self.len == 0 { None } else { Some(self.prefix.clone()) }
+    }
+
+    /// The largest sum of a run ending at the last point.
+    pub fn suffix(&self) -> Option<T> {
+        if self.len == 0 { None } else { Some(self.suffix.clone()) }
+    }
+
+    pub fn total(&self) -> T {
+        self.total.clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct Runs<N, T> {
+    // key and value of the first and of the last point
+    first: Option<(N, T)>,
+    last: Option<(N, T)>,
+    prefix: usize,
+    suffix: usize,
+    best: usize,
+    len: usize,
+}
+
+impl<N: Num+Clone, T: Clone> Runs<N, T> {
+    fn identity() -> Self {
+        Runs { first: None, last: None, prefix: 0, suffix: 0, best: 0, len: 0 }
+    }
+
+    fn of(key: N, value: T) -> Self {
+        Runs {
+            first: Some((key.clone(), value.clone())),
+            last: Some((key, value)),
+            prefix: 1,
+            suffix: 1,
+            best: 1,
+            len: 1,
+        }
+    }
+
+    // The values of the last point of x and the first point of y, if their
+    // keys are adjacent.
+    fn boundary<'a>(x: &'a Self, y: &'a Self) -> Option<(&'a T, &'a T)> {
+        match (x.last.as_ref(), y.first.as_ref()) {
+            (Some((kl, l)), Some((kf, f)))
+                if kl.clone() + N::one() == *kf => Some((l, f)),
+            _ => None
+        }
+    }
+
+    // joined - whether the last point of x continues into the first of y
+    fn combine(x: &Self, y: &Self, joined: bool) -> Self {
+        if x.len == 0 {
+            return y.clone();
+        }
+        if y.len == 0 {
+            return x.clone();
+        }
+        let mut best = max(x.best, y.best);
+        if joined {
+            best = max(best, x.suffix + y.prefix);
+        }
+        Runs {
+            first: x.first.clone(),
+            last: y.last.clone(),
+            prefix: if joined && x.prefix == x.len { x.len + y.prefix } else { x.prefix },
+            suffix: if joined && y.suffix == y.len { y.len + x.suffix } else { y.suffix },
+            best,
+            len: x.len + y.len,
+        }
+    }
+}
+
+/// Longest run of points with consecutive keys and equal values.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct EqualRun<N, T>(Runs<N, T>);
+
+impl<N: Num+Clone, T: Clone+PartialEq> EqualRun<N, T> {
+    pub fn identity() -> Self {
+        EqualRun(Runs::identity())
+    }
+
+    /// The summary of the single point `key`, to be inserted at `key`.
+    pub fn of(key: N, value: T) -> Self {
+        EqualRun(Runs::of(key, value))
+    }
+
+    /// `x` must be the summary of the points directly before the points of `y`.
+    pub fn combine(x: &Self, y: &Self) -> Self {
+        let joined = Runs::boundary(&x.0, &y.0).is_some_and(|(l, f)| l == f);
+        EqualRun(Runs::combine(&x.0, &y.0, joined))
+    }
+
+    /// Length of the longest run of equal values.
+    pub fn best(&self) -> usize {
+        self.0.best
+    }
+
+    /// Length of the run of values equal to the first one.
+    pub fn prefix(&self) -> usize {
+        self.0.prefix
+    }
+
+    /// Length of the run of values equal to the last one.
+    pub fn suffix(&self) -> usize {
+        self.0.suffix
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.len == 0
+    }
+}
+
+/// Longest run of points with consecutive keys and strictly increasing values.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct IncreasingRun<N, T>(Runs<N, T>);
+
+impl<N: Num+Clone, T: Clone+PartialOrd> IncreasingRun<N, T> {
+    pub fn identity() -> Self {
+        IncreasingRun(Runs::identity())
+    }
+
+    /// The summary of the single point `key`, to be inserted at `key`.
+    pub fn of(key: N, value: T) -> Self {
+        IncreasingRun(Runs::of(key, value))
+    }
+
+    /// `x` must be the summary of the points directly before the points of `y`.
+    pub fn combine(x: &Self, y: &Self) -> Self {
+        let joined = Runs::boundary(&x.0, &y.0).is_some_and(|(l, f)| l < f);
+        IncreasingRun(Runs::combine(&x.0, &y.0, joined))
+    }
+
+    /// Length of the longest increasing run.
+    pub fn best(&self) -> usize {
+        self.0.best
+    }
+
+    /// Length of the increasing run starting at the first point.
+    pub fn prefix(&self) -> usize {
+        self.0.prefix
+    }
+
+    /// Length of the increasing run ending at the last point.
+    pub fn suffix(&self) -> usize {
+        self.0.suffix
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.len == 0
+    }
+}
@@ -0,0 +1,92 @@
+// Read-only access to the nodes of the trees, for debugging and for
+// building custom algorithms on top of them.
+
+pub(crate) trait TreeNode<N, V> {
+    fn start(&self) -> &N;
+    fn end(&self) -> &N;
+    fn value(&self) -> &V;
+    fn left(&self) -> Option<&dyn TreeNode<N, V>>;
+    fn right(&self) -> Option<&dyn TreeNode<N, V>>;
+}
+
+/// A node covering points [start, end]. Sons which were never needed are
+/// not allocated, so `left` and `right` may be None for inner nodes too.
+pub struct NodeView<'a, N: 'a, V: 'a> {
+    inner: Inner<'a, N, V>,
+}
+
+enum Inner<'a, N: 'a, V: 'a> {
+    Node(&'a dyn TreeNode<N, V>),
+    // a range the tree has no node for (yet), holding the default
+    Unallocated(N, N, &'a V),
+}
+
+impl<'a, N: 'a, V: 'a> NodeView<'a, N, V> {
+    pub(crate) fn new(node: &'a dyn TreeNode<N, V>) -> Self {
+        NodeView { inner: Inner::Node(node) }
+    }
+
+    pub(crate) fn unallocated(start: N, end: N, default: &'a V) -> Self {
+        NodeView { inner: Inner::Unallocated(start, end, default) }
+    }
+
+    pub fn start(&self) -> &N {
+        match self.inner {
+            Inner::Node(n) => n.start(),
+            Inner::Unallocated(ref start, _, _) => start,
+        }
+    }
+
+    pub fn end(&self) -> &N {
+        match self.inner {
+            Inner::Node(n) => n.end(),
+            Inner::Unallocated(_, ref end, _) => end,
+        }
+    }
+
+    pub fn value(&self) -> &'a V {
+        match self.inner {
+            Inner::Node(n) => n.value(),
+            Inner::Unallocated(_, _, default) => default,
+        }
+    }
+
+    /// Whether the tree has a node for this range. Only `decompose` reports
+    /// ranges without one.
+    pub fn is_allocated(&self) -> bool {
+        match self.inner {
+            Inner::Node(_) => true,
+            Inner::Unallocated(..) => false,
+        }
+    }
+
+    pub fn left(&self) -> Option<NodeView<'a, N, V>> {
+        match self.inner {
+            Inner::Node(n) => n.left().map(NodeView::new),
+            Inner::Unallocated(..) => None,
+        }
+    }
+
+    pub fn right(&self) -> Option<NodeView<'a, N, V>> {
+        match self.inner {
+            Inner::Node(n) => n.right().map(NodeView::new),
+            Inner::Unallocated(..) => None,
+        }
+    }
+
+    pub fn is_leaf(&self) -> bool {
+        self.left().is_none() && self.right().is_none()
+    }
+
+    /// Calls `f` on this node and all allocated nodes below, parents before
+    /// sons, left before right.
+    pub fn visit(&self, f: &mut dyn FnMut(&NodeView<'a, N, V>)) {
+        f(self);
+        if let Some(n) = self.left() {
+            n.visit(f);
+        }
+        if let Some(n) = self.right() {
+            n.visit(f);
+        }
+    }
+}
@@ -0,0 +1,128 @@
+extern crate interval_tree;
+
+mod common;
+
+use common::{Lcg};
+use interval_tree::allocator::{Allocator};
+
+#[test]
+#[should_panic]
+fn invalid_allocator() {
+    let _ = Allocator::new(10, 0);
+}
+
+#[test]
+#[should_panic]
+fn invalid_free() {
+    let mut a = Allocator::new(0, 10);
+    a.free(8, 5);
+}
+
+#[test]
+fn first_fit() {
+    let mut a = Allocator::new(0, 9);
+    assert_eq!(a.bounds(), (0, 9));
+    assert_eq!(a.largest_free(), 10);
+    assert_eq!(a.allocate(3), Some(0));
+    assert_eq!(a.allocate(3), Some(3));
+    assert_eq!(a.allocate(3), Some(6));
+    assert_eq!(a.allocate(2), None);
+    assert_eq!(a.allocate(1), Some(9));
+    assert_eq!(a.largest_free(), 0);
+    assert_eq!(a.allocate(1), None);
+
+    a.free(3, 3);
+    assert_eq!(a.is_free(2), Some(false));
+    assert_eq!(a.is_free(3), Some(true));
+    assert_eq!(a.is_free(10), None);
+    assert_eq!(a.allocate(4), None);
+    assert_eq!(a.allocate(2), Some(3));
+    assert_eq!(a.allocate(1), Some(5));
+}
+
+#[test]
+fn runs_across_nodes() {
+    let mut a = Allocator::new(-8, 7);
+    a.reserve(-8, 16);
+    a.free(-2, 2);
+    a.free(0, 3);
+    a.free(5, 2);
+    assert_eq!(a.largest_free(), 5);
+    assert_eq!(a.allocate(3), Some(-2));
+    assert_eq!(a.allocate(3), None);
+    assert_eq!(a.allocate(2), Some(1));
+    assert_eq!(a.allocate(2), Some(5));
+    a.free(-8, 16);
+    assert_eq!(a.allocate(16), Some(-8));
+}
+
+#[test]
+fn matches_naive() {
+    let size = 64;
+    let mut a = Allocator::new(0, size - 1);
+    let mut used = vec![false; size as usize];
+    let mut rng = Lcg::new(7);
+    for _ in 0..2000 {
+        let len = rng.below(6) + 1;
+        if rng.below(3) < 1 {
+            let start = rng.below(size - len + 1);
+            a.free(start, len);
+            for p in start..start + len {
+                used[p as usize] = false;
+            }
+        } else {
+            let expected = (0..size - len + 1)
+                .find(|&s| (s..s + len).all(|p| !used[p as usize]));
+            assert_eq!(a.allocate(len), expected);
+            if let Some(s) = expected {
+                for p in s..s + len {
+                    used[p as usize] = true;
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn large_bounds() {
+    let mut a = Allocator::new(0, 1_000_000_000i64);
+    assert_eq!(a.allocate(500_000_000), Some(0));
+    assert_eq!(a.allocate(500_000_001), Some(500_000_000));
+    assert_eq!(a.allocate(1), None);
+    a.free(123_456_789, 10);
+    assert_eq!(a.allocate(5), Some(123_456_789));
+    assert_eq!(a.allocate(5), Some(123_456_794));
+}
+
+#[test]
+#[should_panic(expected = "must fit in the bound type")]
+fn bounds_wider_than_type() {
+    let _ = Allocator::new(0u8, 255);
+}
+
+#[test]
+#[should_panic(expected = "outside of bounds")]
+fn run_past_max_value() {
+    let mut a = Allocator::new(1u8, 255);
+    a.free(250, 10);
+}
+
+#[test]
+fn runs_up_to_max_value() {
+    let mut a = Allocator::new(1u8, 255);
+    assert_eq!(a.largest_free(), 255);
+    a.reserve(1, 250);
+    assert_eq!(a.allocate(5), Some(251));
+    assert_eq!(a.allocate(1), None);
+    a.free(254, 2);
+    assert_eq!(a.is_free(255), Some(true));
+    assert_eq!(a.allocate(2), Some(254));
+    a.free(1, 255);
+    assert_eq!(a.allocate(255), Some(1));
+
+    let mut b = Allocator::new(1i8, 127);
+    assert_eq!(b.allocate(120), Some(1));
+    assert_eq!(b.allocate(7), Some(121));
+    b.free(125, 3);
+    assert_eq!(b.largest_free(), 3);
+}
@@ -0,0 +1,24 @@
+// Helpers shared by the integration tests; every test crate includes this
+// with `mod common;` and uses only part of it.
+#![allow(dead_code)]
+
+/// Deterministic pseudo-random numbers (a 64-bit LCG) for comparing trees
+/// with naive implementations.
+pub struct Lcg(u64);
+
+impl Lcg {
+    pub fn new(seed: u64) -> Self {
+        Lcg(seed)
+    }
+
+    /// The next 31 random bits.
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        self.0 >> 33
+    }
+
+    /// A number in [0, modulo).
+    pub fn below(&mut self, modulo: i64) -> i64 {
+        (self.next_u64() % modulo as u64) as i64
+    }
+}
@@ -0,0 +1,79 @@
+extern crate interval_tree;
+
+use interval_tree::hash::{RollingHash, ranges_equal};
+use interval_tree::pointsegment::{PointSegmentTree};
+
+fn text_tree(text: &str) -> PointSegmentTree<i64, RollingHash> {
+    let mut t = PointSegmentTree::new(0, text.len() as i64 - 1, RollingHash::identity(),
+                                      Box::new(RollingHash::combine));
+    for (p, c) in text.bytes().enumerate() {
+        t.insert(p as i64, RollingHash::of(c as u64));
+    }
+    t
+}
+
+#[test]
+fn combine_is_concatenation() {
+    let a = RollingHash::of(1);
+    let b = RollingHash::of(2);
+    let c = RollingHash::of(3);
+    let ab_c = RollingHash::combine(&RollingHash::combine(&a, &b), &c);
+    let a_bc = RollingHash::combine(&a, &RollingHash::combine(&b, &c));
+    assert_eq!(ab_c, a_bc);
+    assert_eq!(ab_c.len(), 3);
+    assert_ne!(RollingHash::combine(&a, &b), RollingHash::combine(&b, &a));
+    assert_eq!(RollingHash::combine(&a, &RollingHash::identity()), a);
+    assert!(RollingHash::identity().is_empty());
+}
+
+#[test]
+fn equal_ranges() {
+    let mut t = text_tree("abracadabra");
+    assert!(ranges_equal(&t, 0, 3, 7, 10));
+    assert!(ranges_equal(&t, 0, 0, 3, 3));
+    assert!(!ranges_equal(&t, 0, 3, 6, 9));
+    assert!(!ranges_equal(&t, 0, 3, 7, 9));
+    assert!(!ranges_equal(&t, 0, 3, 8, 11));
+    t.insert(8, RollingHash::of(b'x' as u64));
+    assert!(!ranges_equal(&t, 0, 3, 7, 10));
+    t.insert(1, RollingHash::of(b'x' as u64));
+    assert!(ranges_equal(&t, 0, 3, 7, 10));
+}
+
+#[test]
+fn matches_naive_comparison() {
+    let text = "abaababaabaababaababaabaababaab";
+    let t = text_tree(text);
+    let n = text.len();
+    for len in 1..n {
+        for a in 0..n - len + 1 {
+            for c in 0..n - len + 1 {
+                let expected = text[a..a + len] == text[c..c + len];
+                assert_eq!(ranges_equal(&t, a as i64, (a + len - 1) as i64,
+                                        c as i64, (c + len - 1) as i64), expected);
+            }
+        }
+    }
+}
+
+#[test]
+fn values_apart_by_product_of_moduli() {
+    let period = 1_000_000_007u64 * 998_244_353;
+    for v in [0, 1, 97, 123_456_789] {
+        assert_ne!(RollingHash::of(v), RollingHash::of(v + period));
+    }
+    assert_ne!(RollingHash::of(u64::MAX), RollingHash::of(u64::MAX - period));
+}
+
+#[test]
+fn ranges_of_different_lengths() {
+    let mut t = PointSegmentTree::new(0, 9, RollingHash::identity(),
+                                      Box::new(RollingHash::combine));
+    for (p, v) in [(0, 1), (1, 2), (5, 1), (8, 2)] {
+        t.insert(p, RollingHash::of(v));
+    }
+    assert_eq!(t.query(0, 1), t.query(5, 9));
+    assert!(!ranges_equal(&t, 0, 1, 5, 9));
+    assert!(!ranges_equal(&t, 5, 9, 0, 1));
+    assert!(!ranges_equal(&t, 1, 0, 1, 0));
+}
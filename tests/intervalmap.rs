@@ -0,0 +1,85 @@
+extern crate interval_tree;
+
+use interval_tree::intervalmap::{IntervalMap};
+
+fn ranges<V: Clone + PartialEq>(m: &IntervalMap<i64, V>) -> Vec<(i64, i64, V)> {
+    m.iter().map(|(s, e, v)| (*s, *e, v.clone())).collect()
+}
+
+#[test]
+fn empty_map() {
+    let m: IntervalMap<i64, char> = IntervalMap::new();
+    assert!(m.is_empty());
+    assert_eq!(m.len(), 0);
+    assert_eq!(m.get(0), None);
+}
+
+#[test]
+#[should_panic]
+fn invalid_insert() {
+    let mut m = IntervalMap::new();
+    m.insert(5, 4, 'a');
+}
+
+#[test]
+fn insert_overrides_and_splits() {
+    let mut m = IntervalMap::new();
+    m.insert(0, 10, 'a');
+    m.insert(3, 5, 'b');
+    assert_eq!(ranges(&m), vec![(0, 2, 'a'), (3, 5, 'b'), (6, 10, 'a')]);
+    assert_eq!(m.get(-1), None);
+    assert_eq!(m.get(2), Some(&'a'));
+    assert_eq!(m.get(5), Some(&'b'));
+    assert_eq!(m.get(10), Some(&'a'));
+    assert_eq!(m.get(11), None);
+    m.insert(4, 8, 'c');
+    assert_eq!(ranges(&m), vec![(0, 2, 'a'), (3, 3, 'b'), (4, 8, 'c'), (9, 10, 'a')]);
+    m.insert(-5, 20, 'd');
+    assert_eq!(ranges(&m), vec![(-5, 20, 'd')]);
+}
+
+#[test]
+fn adjacent_equal_ranges_coalesce() {
+    let mut m = IntervalMap::new();
+    m.insert(0, 2, 1);
+    m.insert(6, 9, 1);
+    m.insert(3, 5, 2);
+    assert_eq!(m.len(), 3);
+    m.insert(3, 5, 1);
+    assert_eq!(ranges(&m), vec![(0, 9, 1)]);
+    m.insert(11, 12, 1);
+    assert_eq!(ranges(&m), vec![(0, 9, 1), (11, 12, 1)]);
+    m.insert(10, 10, 1);
+    assert_eq!(ranges(&m), vec![(0, 12, 1)]);
+}
+
+#[test]
+fn remove_ranges() {
+    let mut m = IntervalMap::new();
+    m.insert(0, 10, 'a');
+    m.insert(20, 30, 'b');
+    m.remove(5, 25);
+    assert_eq!(ranges(&m), vec![(0, 4, 'a'), (26, 30, 'b')]);
+    m.remove(2, 2);
+    assert_eq!(ranges(&m), vec![(0, 1, 'a'), (3, 4, 'a'), (26, 30, 'b')]);
+    m.remove(-100, 100);
+    assert!(m.is_empty());
+    m.insert(0, 1, 'x');
+    m.clear();
+    assert!(m.is_empty());
+}
+
+#[test]
+fn iter_from_point() {
+    let mut m = IntervalMap::new();
+    m.insert(0, 2, 'a');
+    m.insert(5, 8, 'b');
+    m.insert(12, 12, 'c');
+    let from = |p| m.iter_from(p).map(|(s, _, v)| (*s, *v)).collect::<Vec<_>>();
+    assert_eq!(from(-3), vec![(0, 'a'), (5, 'b'), (12, 'c')]);
+    assert_eq!(from(2), vec![(0, 'a'), (5, 'b'), (12, 'c')]);
+    assert_eq!(from(3), vec![(5, 'b'), (12, 'c')]);
+    assert_eq!(from(8), vec![(5, 'b'), (12, 'c')]);
+    assert_eq!(from(9), vec![(12, 'c')]);
+    assert_eq!(from(13), vec![]);
+}
@@ -0,0 +1,78 @@
+extern crate interval_tree;
+
+use interval_tree::intervalset::{IntervalSet};
+
+fn ranges(s: &IntervalSet<i64>) -> Vec<(i64, i64)> {
+    s.iter().map(|(s, e)| (*s, *e)).collect()
+}
+
+#[test]
+fn insert_and_contains() {
+    let mut s = IntervalSet::new();
+    assert!(s.is_empty());
+    s.insert(0, 3);
+    s.insert(5, 7);
+    s.insert(4, 4);
+    assert_eq!(ranges(&s), vec![(0, 7)]);
+    s.insert(10, 12);
+    assert_eq!(s.len(), 2);
+    assert!(s.contains(0));
+    assert!(s.contains(7));
+    assert!(!s.contains(8));
+    assert!(s.contains(11));
+    s.remove(2, 10);
+    assert_eq!(ranges(&s), vec![(0, 1), (11, 12)]);
+}
+
+#[test]
+fn union_and_difference() {
+    let mut a = IntervalSet::new();
+    a.insert(0, 5);
+    a.insert(10, 15);
+    let mut b = IntervalSet::new();
+    b.insert(4, 11);
+    b.insert(20, 21);
+    assert_eq!(ranges(&a.union(&b)), vec![(0, 15), (20, 21)]);
+    assert_eq!(ranges(&a.difference(&b)), vec![(0, 3), (12, 15)]);
+    assert_eq!(ranges(&b.difference(&a)), vec![(6, 9), (20, 21)]);
+    assert!(a.difference(&a).is_empty());
+}
+
+#[test]
+fn gaps_and_complement() {
+    let mut s = IntervalSet::new();
+    s.insert(2, 3);
+    s.insert(6, 8);
+    s.insert(15, 20);
+    assert_eq!(s.gaps(0, 10), vec![(0, 1), (4, 5), (9, 10)]);
+    assert_eq!(s.gaps(3, 7), vec![(4, 5)]);
+    assert_eq!(s.gaps(6, 8), vec![]);
+    assert_eq!(s.gaps(9, 14), vec![(9, 14)]);
+    assert_eq!(s.gaps(-5, 30), vec![(-5, 1), (4, 5), (9, 14), (21, 30)]);
+    assert_eq!(ranges(&s.complement(0, 16)), vec![(0, 1), (4, 5), (9, 14)]);
+    assert_eq!(ranges(&s.complement(0, 16).complement(0, 16)),
+               vec![(2, 3), (6, 8), (15, 16)]);
+}
+
+#[test]
+#[should_panic]
+fn invalid_gaps() {
+    let s: IntervalSet<i64> = IntervalSet::new();
+    s.gaps(5, 4);
+}
+
+#[test]
+fn insert_up_to_max_value() {
+    let ranges = |s: &IntervalSet<u8>| s.iter().map(|(s, e)| (*s, *e)).collect::<Vec<_>>();
+    let mut s = IntervalSet::new();
+    s.insert(0, 5);
+    s.insert(250, 255);
+    assert_eq!(ranges(&s), vec![(0, 5), (250, 255)]);
+    assert!(s.contains(252));
+    assert!(s.contains(255));
+    s.insert(200, 249);
+    s.insert(6, 6);
+    assert_eq!(ranges(&s), vec![(0, 6), (200, 255)]);
+    assert_eq!(ranges(&s.complement(0, 255)), vec![(7, 199)]);
+    assert_eq!(ranges(&s.union(&s.complement(0, 255))), vec![(0, 255)]);
+}
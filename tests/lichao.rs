@@ -0,0 +1,77 @@
+extern crate interval_tree;
+
+mod common;
+
+use common::{Lcg};
+use interval_tree::lichao::{LiChaoTree, Line};
+
+#[test]
+fn queries_on_empty() {
+    let t: LiChaoTree<i64> = LiChaoTree::new_max(0, 10);
+    assert_eq!(t.bounds(), (0, 10));
+    assert_eq!(t.query(5), None);
+    assert_eq!(t.query(11), None);
+}
+
+#[test]
+#[should_panic]
+fn invalid_tree() {
+    let _: LiChaoTree<i64> = LiChaoTree::new_min(10, 0);
+}
+
+#[test]
+#[should_panic]
+fn invalid_insert() {
+    let mut t = LiChaoTree::new_max(0, 10);
+    t.insert_segment(5, 11, 1, 1);
+}
+
+#[test]
+fn lines_max_and_min() {
+    let mut max = LiChaoTree::new_max(-100, 100);
+    let mut min = LiChaoTree::new_min(-100, 100);
+    for &(a, b) in &[(1, 0), (-1, 0), (0, 5), (2, -20)] {
+        max.insert_line(a, b);
+        min.insert_line(a, b);
+    }
+    for x in -100..101 {
+        let values: Vec<i64> = [(1, 0), (-1, 0), (0, 5), (2, -20)].iter()
+            .map(|&(a, b)| Line::new(a, b).at(&x))
+            .collect();
+        assert_eq!(max.query(x), values.iter().cloned().max());
+        assert_eq!(min.query(x), values.iter().cloned().min());
+    }
+}
+
+#[test]
+fn segments_match_naive() {
+    let mut rng = Lcg::new(29);
+    let mut t = LiChaoTree::new_max(-50, 49);
+    let mut segments = vec![];
+    for _ in 0..60 {
+        let s = rng.below(100) - 50;
+        let e = s + rng.below(50 - s);
+        let (a, b) = (rng.below(21) - 10, rng.below(201) - 100);
+        t.insert_segment(s, e, a, b);
+        segments.push((s, e, Line::new(a, b)));
+        for x in -50..50 {
+            let expected = segments.iter()
+                .filter(|seg| seg.0 <= x && x <= seg.1)
+                .map(|seg| seg.2.at(&x))
+                .max();
+            assert_eq!(t.query(x), expected);
+        }
+    }
+}
+
+#[test]
+fn large_bounds() {
+    let mut t = LiChaoTree::new_min(0, 1_000_000_000i64);
+    t.insert_line(-3, 3_000_000_000);
+    t.insert_segment(500_000_000, 600_000_000, 0, 0);
+    t.insert_line(1, 0);
+    assert_eq!(t.query(0), Some(0));
+    assert_eq!(t.query(750_000_000), Some(750_000_000));
+    assert_eq!(t.query(800_000_000), Some(600_000_000));
+    assert_eq!(t.query(550_000_000), Some(0));
+}
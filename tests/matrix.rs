@@ -0,0 +1,96 @@
+extern crate interval_tree;
+
+mod common;
+
+use common::{Lcg};
+use interval_tree::matrix::{Matrix, Arithmetic, MinPlus, MaxPlus};
+use interval_tree::pointsegment::{PointSegmentTree};
+
+type M2 = Matrix<Arithmetic<i64>, 2>;
+
+#[test]
+fn identity_and_product() {
+    let a = M2::from_rows([[1, 2], [3, 4]]);
+    let b = M2::from_rows([[0, 1], [1, 0]]);
+    assert_eq!(M2::combine(&a, &M2::identity()), a);
+    assert_eq!(M2::combine(&M2::identity(), &a), a);
+    assert_eq!(M2::combine(&a, &b), M2::from_rows([[2, 1], [4, 3]]));
+    assert_eq!(M2::combine(&b, &a), M2::from_rows([[3, 4], [1, 2]]));
+    assert_eq!(*a.get(1, 0), 3);
+    assert_eq!(a.rows(), &[[1, 2], [3, 4]]);
+}
+
+#[test]
+fn ordered_products_in_tree() {
+    let mut rng = Lcg::new(5);
+    let n = 30;
+    let mut t = PointSegmentTree::new(0, n - 1, Matrix::identity(),
+                                      Box::new(Matrix::combine));
+    let mut values: Vec<Matrix<Arithmetic<i64>, 3>> = vec![];
+    for p in 0..n {
+        let mut rows = [[0; 3]; 3];
+        for row in rows.iter_mut() {
+            for x in row.iter_mut() {
+                *x = rng.below(5) - 2;
+            }
+        }
+        let m = Matrix::from_rows(rows);
+        t.insert(p, m.clone());
+        values.push(m);
+    }
+    for l in 0..n {
+        for r in l..n {
+            let expected = values[l as usize..(r + 1) as usize].iter()
+                .fold(Matrix::identity(), |acc, m| Matrix::combine(&acc, m));
+            assert_eq!(t.query(l, r), Some(expected));
+        }
+    }
+}
+
+#[test]
+fn fibonacci() {
+    let mut t = PointSegmentTree::new(0, 1000, M2::identity(), Box::new(M2::combine));
+    for p in 0..90 {
+        t.insert(p, M2::from_rows([[1, 1], [1, 0]]));
+    }
+    assert_eq!(*t.query(0, 9).unwrap().get(0, 1), 55);
+    assert_eq!(*t.query(0, 89).unwrap().get(0, 1), 2_880_067_194_370_816_120);
+    assert_eq!(t.query(100, 1000), Some(M2::identity()));
+}
+
+#[test]
+fn shortest_and_longest_paths() {
+    // layered graph with two vertices per layer; a point is the matrix of
+    // edge weights between consecutive layers
+    const INF: i64 = i64::MAX;
+    let layers = [[[1, 5], [2, INF]], [[INF, 1], [3, 1]], [[4, 2], [1, 1]]];
+    let mut short = PointSegmentTree::new(0, 2, Matrix::identity(),
+                                          Box::new(Matrix::<MinPlus<i64>, 2>::combine));
+    for (p, rows) in layers.iter().enumerate() {
+        short.insert(p as i64, Matrix::from_rows(*rows));
+    }
+    let all = short.query(0, 2).unwrap();
+    assert_eq!(all.rows(), &[[3, 3], [4, 4]]);
+    assert_eq!(short.query(0, 1).unwrap().rows(), &[[8, 2], [INF, 3]]);
+
+    let mut long = PointSegmentTree::new(0, 2, Matrix::identity(),
+                                         Box::new(Matrix::<MaxPlus<i64>, 2>::combine));
+    for (p, rows) in layers.iter().enumerate() {
+        let rows = rows.map(|row| row.map(|x| if x == INF { i64::MIN } else { x }));
+        long.insert(p as i64, Matrix::from_rows(rows));
+    }
+    assert_eq!(long.query(0, 2).unwrap().rows(), &[[12, 10], [4, 4]]);
+    assert_eq!(*long.query(1, 1).unwrap().get(0, 0), i64::MIN);
+}
+
+#[test]
+fn overflowing_paths_saturate() {
+    type Short = Matrix<MinPlus<i64>, 1>;
+    type Long = Matrix<MaxPlus<i64>, 1>;
+    let big = Short::from_rows([[i64::MAX - 1]]);
+    assert_eq!(*Short::combine(&big, &Short::from_rows([[5]])).get(0, 0), i64::MAX);
+    assert_eq!(*Short::combine(&big, &Short::from_rows([[-5]])).get(0, 0), i64::MAX - 6);
+    let small = Long::from_rows([[i64::MIN + 1]]);
+    assert_eq!(*Long::combine(&small, &Long::from_rows([[-5]])).get(0, 0), i64::MIN);
+    assert_eq!(*Long::combine(&small, &Long::from_rows([[5]])).get(0, 0), i64::MIN + 6);
+}
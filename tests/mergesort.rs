@@ -0,0 +1,52 @@
+extern crate interval_tree;
+
+mod common;
+
+use common::{Lcg};
+use interval_tree::mergesort::{MergeSortTree};
+
+#[test]
+#[should_panic]
+fn empty_tree() {
+    let _: MergeSortTree<i64> = MergeSortTree::new(&[]);
+}
+
+#[test]
+fn small_queries() {
+    let t = MergeSortTree::new(&[5, 1, 4, 1, 9, 2, 6]);
+    assert_eq!(t.bounds(), (0, 6));
+    assert_eq!(t.kth_smallest(0, 6, 0), Some(1));
+    assert_eq!(t.kth_smallest(0, 6, 1), Some(1));
+    assert_eq!(t.kth_smallest(0, 6, 2), Some(2));
+    assert_eq!(t.kth_smallest(0, 6, 6), Some(9));
+    assert_eq!(t.kth_smallest(0, 6, 7), None);
+    assert_eq!(t.kth_smallest(2, 4, 1), Some(4));
+    assert_eq!(t.kth_smallest(4, 4, 0), Some(9));
+    assert_eq!(t.kth_smallest(4, 7, 0), None);
+    assert_eq!(t.kth_smallest(4, 3, 0), None);
+    assert_eq!(t.count_in_range(0, 6, &1, &4), Some(4));
+    assert_eq!(t.count_in_range(1, 5, &2, &9), Some(3));
+    assert_eq!(t.count_in_range(1, 5, &10, &20), Some(0));
+    assert_eq!(t.count_in_range(1, 5, &5, &4), Some(0));
+    assert_eq!(t.count_in_range(1, 7, &1, &4), None);
+}
+
+#[test]
+fn matches_naive() {
+    let mut rng = Lcg::new(23);
+    let values: Vec<u64> = (0..60).map(|_| rng.next_u64() % 20).collect();
+    let t = MergeSortTree::new(&values);
+    for l in 0..60 {
+        for r in l..60 {
+            let mut sorted = values[l..r + 1].to_vec();
+            sorted.sort();
+            for (k, v) in sorted.iter().enumerate() {
+                assert_eq!(t.kth_smallest(l, r, k), Some(*v));
+            }
+            for &(low, high) in &[(0, 19), (3, 7), (10, 10), (15, 30)] {
+                let expected = sorted.iter().filter(|v| low <= **v && **v <= high).count();
+                assert_eq!(t.count_in_range(l, r, &low, &high), Some(expected));
+            }
+        }
+    }
+}
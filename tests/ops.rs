@@ -0,0 +1,135 @@
+extern crate interval_tree;
+
+use interval_tree::ops::*;
+use interval_tree::pointsegment::{PointSegmentTree};
+use interval_tree::segmentpoint::{SegmentPointTree};
+
+#[test]
+fn arithmetic_presets() {
+    let mut sum: PointSegmentTree<i64, i64> =
+        PointSegmentTree::new(0, 10, Sum::identity(), Box::new(Sum::combine));
+    let mut product: PointSegmentTree<i64, i64> =
+        PointSegmentTree::new(0, 10, Product::identity(), Box::new(Product::combine));
+    let mut min: PointSegmentTree<i64, i64> =
+        PointSegmentTree::new(0, 10, Min::identity(), Box::new(Min::combine));
+    let mut max: PointSegmentTree<i64, i64> =
+        PointSegmentTree::new(0, 10, Max::identity(), Box::new(Max::combine));
+    let mut float_min: PointSegmentTree<i64, f64> =
+        PointSegmentTree::new(0, 10, FloatMin::identity(), Box::new(FloatMin::combine));
+    let mut float_max: PointSegmentTree<i64, f64> =
+        PointSegmentTree::new(0, 10, FloatMax::identity(), Box::new(FloatMax::combine));
+    for (p, v) in [(1, 3), (4, -2), (5, 5), (9, 4)] {
+        sum.insert(p, v);
+        product.insert(p, v);
+        min.insert(p, v);
+        max.insert(p, v);
+        float_min.insert(p, v as f64);
+        float_max.insert(p, v as f64);
+    }
+    assert_eq!(sum.query(0, 10), Some(10));
+    assert_eq!(sum.query(2, 3), Some(0));
+    assert_eq!(product.query(0, 10), Some(-120));
+    assert_eq!(product.query(2, 3), Some(1));
+    assert_eq!(min.query(0, 10), Some(-2));
+    assert_eq!(min.query(5, 10), Some(4));
+    assert_eq!(min.query(6, 8), Some(i64::MAX));
+    assert_eq!(max.query(0, 10), Some(5));
+    assert_eq!(max.query(6, 8), Some(i64::MIN));
+    assert_eq!(float_min.query(0, 10), Some(-2.));
+    assert_eq!(float_min.query(6, 8), Some(f64::INFINITY));
+    assert_eq!(float_max.query(0, 10), Some(5.));
+    assert_eq!(float_max.query(6, 8), Some(f64::NEG_INFINITY));
+}
+
+#[test]
+fn integer_presets() {
+    let mut gcd: PointSegmentTree<i64, u32> =
+        PointSegmentTree::new(0, 10, Gcd::identity(), Box::new(Gcd::combine));
+    let mut lcm: PointSegmentTree<i64, u32> =
+        PointSegmentTree::new(0, 10, Lcm::identity(), Box::new(Lcm::combine));
+    let mut and: PointSegmentTree<i64, u8> =
+        PointSegmentTree::new(0, 10, BitAnd::identity(), Box::new(BitAnd::combine));
+    let mut or: PointSegmentTree<i64, u8> =
+        PointSegmentTree::new(0, 10, BitOr::identity(), Box::new(BitOr::combine));
+    let mut xor: PointSegmentTree<i64, u8> =
+        PointSegmentTree::new(0, 10, Xor::identity(), Box::new(Xor::combine));
+    for (p, v) in [(0, 12u32), (3, 18), (7, 8)] {
+        gcd.insert(p, v);
+        lcm.insert(p, v);
+    }
+    assert_eq!(gcd.query(0, 10), Some(2));
+    assert_eq!(gcd.query(0, 3), Some(6));
+    assert_eq!(gcd.query(4, 6), Some(0));
+    assert_eq!(lcm.query(0, 10), Some(72));
+    assert_eq!(lcm.query(0, 3), Some(36));
+    assert_eq!(lcm.query(4, 6), Some(1));
+    for (p, v) in [(2, 0b1100u8), (5, 0b1010), (6, 0b0110)] {
+        and.insert(p, v);
+        or.insert(p, v);
+        xor.insert(p, v);
+    }
+    assert_eq!(and.query(0, 10), Some(0b0000));
+    assert_eq!(and.query(2, 5), Some(0b1000));
+    assert_eq!(and.query(7, 10), Some(0xff));
+    assert_eq!(or.query(0, 10), Some(0b1110));
+    assert_eq!(or.query(5, 6), Some(0b1110));
+    assert_eq!(xor.query(0, 10), Some(0b0000));
+    assert_eq!(xor.query(2, 5), Some(0b0110));
+}
+
+#[test]
+fn positional_presets() {
+    let mut count: PointSegmentTree<i64, usize> =
+        PointSegmentTree::new(0, 10, Count::identity(), Box::new(Count::combine));
+    let mut min: PointSegmentTree<i64, Option<(i64, i64)>> =
+        PointSegmentTree::new(0, 10, MinWithIndex::identity(), Box::new(MinWithIndex::combine));
+    let mut max: PointSegmentTree<i64, Option<(i64, i64)>> =
+        PointSegmentTree::new(0, 10, MaxWithIndex::identity(), Box::new(MaxWithIndex::combine));
+    let mut first: PointSegmentTree<i64, Option<char>> =
+        PointSegmentTree::new(0, 10, First::identity(), Box::new(First::combine));
+    let mut last: PointSegmentTree<i64, Option<char>> =
+        PointSegmentTree::new(0, 10, Last::identity(), Box::new(Last::combine));
+    for (p, v, c) in [(1, 5, 'a'), (3, 2, 'b'), (4, 5, 'c'), (8, 2, 'd')] {
+        count.insert(p, 1);
+        min.insert(p, Some((v, p)));
+        max.insert(p, Some((v, p)));
+        first.insert(p, Some(c));
+        last.insert(p, Some(c));
+    }
+    assert_eq!(count.query(0, 10), Some(4));
+    assert_eq!(count.query(2, 7), Some(2));
+    assert_eq!(min.query(0, 10), Some(Some((2, 3))));
+    assert_eq!(min.query(4, 10), Some(Some((2, 8))));
+    assert_eq!(min.query(5, 7), Some(None));
+    assert_eq!(max.query(0, 10), Some(Some((5, 1))));
+    assert_eq!(max.query(2, 10), Some(Some((5, 4))));
+    assert_eq!(first.query(0, 10), Some(Some('a')));
+    assert_eq!(first.query(2, 10), Some(Some('b')));
+    assert_eq!(last.query(0, 10), Some(Some('d')));
+    assert_eq!(last.query(0, 7), Some(Some('c')));
+    assert_eq!(last.query(5, 7), Some(None));
+}
+
+#[test]
+fn segment_point_presets() {
+    let mut sum: SegmentPointTree<i64, i64> =
+        SegmentPointTree::new(0, 10, Sum::identity(), Box::new(Sum::combine));
+    let mut max: SegmentPointTree<i64, i64> =
+        SegmentPointTree::new(0, 10, Max::identity(), Box::new(Max::combine));
+    let mut last: SegmentPointTree<i64, Option<i64>> =
+        SegmentPointTree::new_ordered(0, 10, Last::identity(), Box::new(Last::combine));
+    for (s, e, v) in [(0, 5, 3), (3, 9, 1), (4, 4, 7)] {
+        sum.insert(s, e, v);
+        max.insert(s, e, v);
+        last.insert(s, e, Some(v));
+    }
+    let sums = vec![3, 3, 3, 4, 11, 4, 1, 1, 1, 1, 0];
+    let maxs = vec![3, 3, 3, 3, 7, 3, 1, 1, 1, 1, i64::MIN];
+    let lasts = vec![Some(3), Some(3), Some(3), Some(1), Some(7), Some(1),
+                     Some(1), Some(1), Some(1), Some(1), None];
+    for p in 0..11 {
+        assert_eq!(sum.query(p), Some(sums[p as usize]));
+        assert_eq!(max.query(p), Some(maxs[p as usize]));
+        assert_eq!(last.query(p), Some(lasts[p as usize]));
+    }
+}
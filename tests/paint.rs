@@ -0,0 +1,75 @@
+extern crate interval_tree;
+
+use interval_tree::paint::{PaintTree};
+
+#[test]
+fn queries_on_empty() {
+    let t = PaintTree::new(0, 10, 'x');
+    assert_eq!(t.query(-1), None);
+    assert_eq!(t.query(11), None);
+    assert_eq!(t.query(0), Some('x'));
+    assert_eq!(t.query(10), Some('x'));
+    assert_eq!(t.runs(0, 10).unwrap().collect::<Vec<_>>(), vec![(0, 10, 'x')]);
+    assert!(t.runs(5, 4).is_none());
+    assert!(t.runs(-1, 4).is_none());
+    assert!(t.runs(5, 11).is_none());
+}
+
+#[test]
+#[should_panic]
+fn invalid_tree() {
+    let _ = PaintTree::new(10, 0, 0);
+}
+
+#[test]
+#[should_panic]
+fn invalid_assign() {
+    let mut t = PaintTree::new(0, 10, 0);
+    t.assign(5, 11, 1);
+}
+
+#[test]
+fn assign_overrides() {
+    let mut t = PaintTree::new(0, 10, 0);
+    t.assign(0, 10, 1);
+    t.assign(3, 6, 2);
+    t.assign(5, 8, 3);
+    t.assign(10, 10, 4);
+    assert_eq!(t.bounds(), (0, 10));
+    let values = vec![1, 1, 1, 2, 2, 3, 3, 3, 3, 1, 4];
+    for (p, v) in (0..11).zip(values) {
+        assert_eq!(t.query(p), Some(v));
+    }
+    assert_eq!(t.runs(0, 10).unwrap().collect::<Vec<_>>(),
+               vec![(0, 2, 1), (3, 4, 2), (5, 8, 3), (9, 9, 1), (10, 10, 4)]);
+    assert_eq!(t.runs(4, 6).unwrap().collect::<Vec<_>>(),
+               vec![(4, 4, 2), (5, 6, 3)]);
+}
+
+#[test]
+fn runs_are_maximal() {
+    let mut t = PaintTree::new(-7, 20, "free");
+    t.assign(-7, -3, "a");
+    t.assign(-2, 4, "a");
+    t.assign(10, 12, "b");
+    t.assign(13, 13, "b");
+    t.assign(0, 1, "free");
+    assert_eq!(t.runs(-7, 20).unwrap().collect::<Vec<_>>(),
+               vec![(-7, -1, "a"), (0, 1, "free"), (2, 4, "a"),
+                    (5, 9, "free"), (10, 13, "b"), (14, 20, "free")]);
+    t.assign(-7, 20, "c");
+    assert_eq!(t.runs(-7, 20).unwrap().collect::<Vec<_>>(), vec![(-7, 20, "c")]);
+}
+
+#[test]
+fn large_bounds() {
+    let mut t = PaintTree::new(0, 1_000_000_000i64, 0);
+    t.assign(1000, 2_000_000, 1);
+    t.assign(1_500_000, 3_000_000, 2);
+    assert_eq!(t.query(999), Some(0));
+    assert_eq!(t.query(1_499_999), Some(1));
+    assert_eq!(t.query(2_500_000), Some(2));
+    assert_eq!(t.runs(0, 1_000_000_000).unwrap().collect::<Vec<_>>(),
+               vec![(0, 999, 0), (1000, 1_499_999, 1),
+                    (1_500_000, 3_000_000, 2), (3_000_001, 1_000_000_000, 0)]);
+}
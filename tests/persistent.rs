@@ -0,0 +1,76 @@
+extern crate interval_tree;
+
+mod common;
+
+use common::{Lcg};
+use interval_tree::persistent::{PersistentPointSegmentTree};
+use std::cmp::{max, min};
+
+fn concat(x: &String, y: &String) -> String {
+    format!("{}{}", x, y)
+}
+
+fn naive_query(naive: &[Option<char>], l: usize, r: usize) -> String {
+    naive[l..r + 1].iter().flatten().collect()
+}
+
+#[test]
+fn copies_keep_old_versions() {
+    let mut t = PersistentPointSegmentTree::new(0, 15, String::new(), Box::new(concat));
+    t.insert(1, "a".to_string());
+    t.insert(3, "b".to_string());
+    t.insert(9, "c".to_string());
+    assert_eq!(t.query(0, 15), Some("abc".to_string()));
+    assert_eq!(t.query(4, 3), None);
+    assert_eq!(t.query(0, 16), None);
+
+    let old = t.clone();
+    // aligned: [0, 7] sits in the tree like [8, 15]
+    t.copy_range(0, 7, 8);
+    assert_eq!(t.query(0, 7), Some("ab".to_string()));
+    assert_eq!(t.query(8, 15), Some("ab".to_string()));
+    assert_eq!(t.query(9, 9), Some("a".to_string()));
+    assert_eq!(old.query(8, 15), Some("c".to_string()));
+
+    // unset source points unset the destination
+    t.copy_range(4, 6, 0);
+    assert_eq!(t.query(0, 7), Some("b".to_string()));
+    assert_eq!(old.query(0, 15), Some("abc".to_string()));
+}
+
+#[test]
+fn copies_match_naive() {
+    let mut rng = Lcg::new(29);
+    let mut t = PersistentPointSegmentTree::new(0, 39, String::new(), Box::new(concat));
+    let mut naive: Vec<Option<char>> = vec![None; 40];
+    let mut versions = Vec::new();
+    for _ in 0..200 {
+        if rng.below(3) == 0 {
+            let (p, c) = (rng.below(40), (b'a' + rng.below(26) as u8) as char);
+            t.insert(p, c.to_string());
+            naive[p as usize] = Some(c);
+        } else {
+            let len = rng.below(12);
+            let (src, dst) = (rng.below(40 - len), rng.below(40 - len));
+            t.copy_range(src, src + len, dst);
+            let copied = naive[src as usize..(src + len) as usize + 1].to_vec();
+            naive[dst as usize..(dst + len) as usize + 1].clone_from_slice(&copied);
+        }
+        let (a, b) = (rng.below(40), rng.below(40));
+        let (l, r) = (min(a, b), max(a, b));
+        assert_eq!(t.query(l, r), Some(naive_query(&naive, l as usize, r as usize)));
+        versions.push((t.clone(), naive.clone()));
+    }
+    for (version, naive) in versions.iter().step_by(17) {
+        for l in 0..40 {
+            assert_eq!(version.query(l, 39), Some(naive_query(naive, l as usize, 39)));
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn copy_past_upper_bound() {
+    let mut t = PersistentPointSegmentTree::new(0, 10, 0, Box::new(|x: &i64, y: &i64| x + y));
+    t.copy_range(0, 3, 8);
+}
@@ -1,7 +1,10 @@
 extern crate interval_tree;
 
-use interval_tree::pointsegment::{PointSegmentTree};
-use std::cmp::{max};
+mod common;
+
+use common::{Lcg};
+use interval_tree::pointsegment::{PointSegmentTree, ScanItem, TieBreak};
+use std::cmp::{max, min};
 
 #[test]
 fn queries_on_empty() { 
@@ -52,6 +55,52 @@ fn singleton_tree() {
     assert_eq!(t.query(5, 5), Some(100));
 }
 
+#[test]
+fn singleton_root_before_and_after_insert() {
+    // the default is larger than the inserted value, so a never inserted
+    // root can't pass for an inserted point
+    let build = || PointSegmentTree::new(5, 5, 1, Box::new(|x: &i64, y: &i64| max(*x, *y)));
+    let mut t = build();
+    assert_eq!(t.query(5, 5), Some(1));
+    assert_eq!(t.query_ranges(&[(5, 5)]), Some(1));
+    assert_eq!(t.query_with_position(5, 5), None);
+    assert_eq!(t.top_k(5, 5, 3), Some(vec![]));
+    assert_eq!(t.report(5, 5, |_| true, |_| true).unwrap().collect::<Vec<_>>(), vec![]);
+    assert_eq!((t.first(), t.last()), (None, None));
+    assert_eq!((t.successor(5), t.predecessor(5)), (None, None));
+    assert_eq!(t.scan(5, 5).unwrap().collect::<Vec<_>>(), vec![ScanItem::Unset(5, 5, 1)]);
+    assert_eq!((t.pop_max(5, 5), t.pop_min(5, 5)), (None, None));
+    assert_eq!(build().merge(build(), |x, y| x * 100 + y).first(), None);
+    let sum = PointSegmentTree::new(5, 5, 1, Box::new(|x: &i64, y: &i64| x + y));
+    assert_eq!(sum.query(5, 5), Some(1));
+    assert_eq!(sum.query_ranges(&[(5, 5)]), Some(1));
+
+    t.insert(5, 0);
+    assert_eq!(t.query(5, 5), Some(1));
+    assert_eq!(t.query_with_position(5, 5), Some((5, 0)));
+    assert_eq!(t.top_k(5, 5, 3), Some(vec![(5, 0)]));
+    assert_eq!(t.report(5, 5, |_| true, |_| true).unwrap().collect::<Vec<_>>(), vec![(5, 0)]);
+    assert_eq!((t.first(), t.last()), (Some((5, 0)), Some((5, 0))));
+    assert_eq!((t.successor(4), t.predecessor(6)), (Some((5, 0)), Some((5, 0))));
+    assert_eq!(t.scan(5, 5).unwrap().collect::<Vec<_>>(), vec![ScanItem::Point(5, 1)]);
+
+    assert_eq!(build().merge(t, |x, y| x * 100 + y).first(), Some((5, 0)));
+    let (mut a, mut b) = (build(), build());
+    a.insert(5, 3);
+    b.insert(5, 4);
+    assert_eq!(a.merge(build(), |x, y| x * 100 + y).first(), Some((5, 3)));
+    let mut a = build();
+    a.insert(5, 3);
+    assert_eq!(a.merge(b, |x, y| x * 100 + y).first(), Some((5, 304)));
+
+    let mut t = build();
+    t.insert(5, 3);
+    assert_eq!(t.pop_max(5, 5), Some((5, 3)));
+    assert_eq!(t.query(5, 5), Some(1));
+    assert_eq!(t.pop_max(5, 5), None);
+    assert_eq!(t.first(), None);
+}
+
 #[test]
 fn test_bounds() {
     let t = PointSegmentTree::new(3, 15, 0,
@@ -147,7 +196,7 @@ fn large_queries() {
     assert_eq!(t.query(50, 350_000), Some(3));
 
     // TODO: use step_by when it's stable
-    for i in (1..10) {
+    for i in 1..10 {
         t.insert(i * 1000, 1000);
     }
 
@@ -163,3 +212,469 @@ fn large_queries() {
     assert_eq!(t.query(50, 900_250), Some(9103));
     assert_eq!(t.query(250, 999_000), Some(8101));
 }
+
+#[test]
+fn query_folds_in_key_order() {
+    let concat_f = Box::new(|x: &String, y: &String| x.clone() + y);
+    let mut t = PointSegmentTree::new(0, 20, "".to_string(), concat_f);
+    let letters = "abcdefghijklmnopqrstu";
+    for (p, c) in letters.chars().enumerate() {
+        t.insert(p as i64, c.to_string());
+    }
+    assert_eq!(t.query(0, 20), Some(letters.to_string()));
+    assert_eq!(t.query(3, 13), Some(letters[3..14].to_string()));
+    assert_eq!(t.query(9, 11), Some("jkl".to_string()));
+}
+
+#[test]
+fn query_with_position() {
+    let mut t = PointSegmentTree::new(-5, 20, 0,
+                                      Box::new(|x: &i64, y: &i64| max(*x, *y)));
+    assert_eq!(t.tie_break(), TieBreak::Leftmost);
+    assert_eq!(t.query_with_position(-5, 20), None);
+    t.insert(-3, 4);
+    t.insert(2, 9);
+    t.insert(7, 1);
+    t.insert(11, 9);
+    t.insert(19, -2);
+    assert_eq!(t.query_with_position(-5, 20), Some((2, 9)));
+    assert_eq!(t.query_with_position(3, 20), Some((11, 9)));
+    assert_eq!(t.query_with_position(3, 10), Some((7, 1)));
+    assert_eq!(t.query_with_position(12, 20), Some((19, -2)));
+    assert_eq!(t.query_with_position(12, 18), None);
+    assert_eq!(t.query_with_position(-6, 18), None);
+    assert_eq!(t.query_with_position(5, 4), None);
+    t.set_tie_break(TieBreak::Rightmost);
+    assert_eq!(t.query_with_position(-5, 20), Some((11, 9)));
+    assert_eq!(t.query_with_position(-5, 10), Some((2, 9)));
+}
+
+#[test]
+fn query_with_position_matches_naive() {
+    let mut values = vec![None; 100];
+    let mut t = PointSegmentTree::new(0, 99, i64::MAX,
+                                      Box::new(|x: &i64, y: &i64| min(*x, *y)));
+    let mut rng = Lcg::new(1);
+    for _ in 0..60 {
+        let p = rng.below(100) as usize;
+        let v = rng.below(10);
+        t.insert(p as i64, v);
+        values[p] = Some(v);
+    }
+    for &tie in &[TieBreak::Leftmost, TieBreak::Rightmost] {
+        t.set_tie_break(tie);
+        for l in 0..100 {
+            for r in l..100 {
+                let mut set: Vec<(i64, i64)> = (l..r + 1)
+                    .filter_map(|p| values[p].map(|v| (p as i64, v)))
+                    .collect();
+                if tie == TieBreak::Rightmost {
+                    set.reverse();
+                }
+                let best = set.iter().map(|x| x.1).min();
+                let expected = set.into_iter().find(|x| Some(x.1) == best);
+                assert_eq!(t.query_with_position(l as i64, r as i64), expected);
+            }
+        }
+    }
+}
+
+#[test]
+fn top_k() {
+    let mut t = PointSegmentTree::new(0, 1000, i64::MIN,
+                                      Box::new(|x: &i64, y: &i64| max(*x, *y)));
+    assert_eq!(t.top_k(0, 1000, 3), Some(vec![]));
+    t.insert(10, 5);
+    t.insert(20, 8);
+    t.insert(30, 5);
+    t.insert(500, 1);
+    t.insert(999, 8);
+    t.insert(1000, -4);
+    assert_eq!(t.top_k(0, 1000, 3), Some(vec![(20, 8), (999, 8), (10, 5)]));
+    assert_eq!(t.top_k(0, 1000, 0), Some(vec![]));
+    assert_eq!(t.top_k(0, 1000, 10),
+               Some(vec![(20, 8), (999, 8), (10, 5), (30, 5), (500, 1), (1000, -4)]));
+    assert_eq!(t.top_k(21, 998, 2), Some(vec![(30, 5), (500, 1)]));
+    assert_eq!(t.top_k(31, 499, 2), Some(vec![]));
+    assert_eq!(t.top_k(0, 1001, 2), None);
+}
+
+#[test]
+fn top_k_matches_naive() {
+    let mut values = vec![None; 64];
+    let mut t = PointSegmentTree::new(0, 63, 0,
+                                      Box::new(|x: &u64, y: &u64| max(*x, *y)));
+    let mut rng = Lcg::new(3);
+    for _ in 0..40 {
+        let p = rng.below(64) as usize;
+        let v = rng.next_u64() % 16;
+        t.insert(p as i64, v);
+        values[p] = Some(v);
+    }
+    for l in 0..64 {
+        for r in l..64 {
+            let mut set: Vec<(i64, u64)> = (l..r + 1)
+                .filter_map(|p| values[p].map(|v| (p as i64, v)))
+                .collect();
+            set.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+            set.truncate(5);
+            assert_eq!(t.top_k(l as i64, r as i64, 5), Some(set));
+        }
+    }
+}
+
+#[test]
+fn report() {
+    let mut t = PointSegmentTree::new(0, 1_000_000, 0,
+                                      Box::new(|x: &i64, y: &i64| max(*x, *y)));
+    t.insert(5, 10);
+    t.insert(17, 3);
+    t.insert(1000, 12);
+    t.insert(5000, 7);
+    t.insert(999_999, 30);
+    let above = |t: &PointSegmentTree<i64, i64>, l, r, min_value: i64| -> Vec<(i64, i64)> {
+        t.report(l, r, |m| *m > min_value, |v| *v > min_value).unwrap().collect()
+    };
+    assert_eq!(above(&t, 0, 1_000_000, 5), vec![(5, 10), (1000, 12), (5000, 7), (999_999, 30)]);
+    assert_eq!(above(&t, 0, 1_000_000, 10), vec![(1000, 12), (999_999, 30)]);
+    assert_eq!(above(&t, 6, 999_998, 0), vec![(17, 3), (1000, 12), (5000, 7)]);
+    assert_eq!(above(&t, 0, 1_000_000, 30), vec![]);
+    assert!(t.report(0, 1_000_001, |_| true, |_| true).is_none());
+
+    // the predicate on nodes prunes; the one on points decides
+    let mut visited = 0;
+    let even: Vec<_> = t.report(0, 1_000_000, |_| { visited += 1; true }, |v| v % 2 == 0)
+        .unwrap().collect();
+    assert_eq!(even, vec![(5, 10), (1000, 12), (999_999, 30)]);
+    assert!(visited > 5);
+    let mut visited = 0;
+    let none: Vec<_> = t.report(0, 1_000_000, |m| { visited += 1; *m > 100 }, |_| true)
+        .unwrap().collect();
+    assert_eq!(none, vec![]);
+    assert_eq!(visited, 1);
+}
+
+#[test]
+fn pop_max_and_min() {
+    let mut t = PointSegmentTree::new(0, 100, 0,
+                                      Box::new(|x: &i64, y: &i64| max(*x, *y)));
+    for &(p, v) in &[(3, 4), (10, 9), (20, 9), (50, 2), (99, 7)] {
+        t.insert(p, v);
+    }
+    assert_eq!(t.pop_max(0, 100), Some((10, 9)));
+    assert_eq!(t.query(10, 10), Some(0));
+    assert_eq!(t.query(0, 100), Some(9));
+    assert_eq!(t.pop_max(0, 100), Some((20, 9)));
+    assert_eq!(t.pop_max(0, 60), Some((3, 4)));
+    assert_eq!(t.pop_max(4, 60), Some((50, 2)));
+    assert_eq!(t.pop_max(4, 60), None);
+    assert_eq!(t.pop_max(4, 101), None);
+    assert_eq!(t.query(0, 100), Some(7));
+    assert_eq!(t.pop_max(0, 100), Some((99, 7)));
+    assert_eq!(t.pop_max(0, 100), None);
+    assert_eq!(t.query(0, 100), Some(0));
+    t.insert(42, 1);
+    assert_eq!(t.query_with_position(0, 100), Some((42, 1)));
+
+    let mut t = PointSegmentTree::new(-10, 10, i64::MAX,
+                                      Box::new(|x: &i64, y: &i64| min(*x, *y)));
+    for &(p, v) in &[(-10, 4), (-1, 3), (0, 8), (10, 3)] {
+        t.insert(p, v);
+    }
+    let mut popped = vec![];
+    while let Some(x) = t.pop_min(-10, 10) {
+        popped.push(x);
+    }
+    assert_eq!(popped, vec![(-1, 3), (10, 3), (-10, 4), (0, 8)]);
+    assert_eq!(t.query(-10, 10), Some(i64::MAX));
+}
+
+#[test]
+fn pop_matches_naive() {
+    let mut rng = Lcg::new(31);
+    let mut values = vec![None; 50];
+    let mut t = PointSegmentTree::new(0, 49, 0, Box::new(|x: &i64, y: &i64| max(*x, *y)));
+    for _ in 0..300 {
+        if rng.below(2) < 1 {
+            let (p, v) = (rng.below(50), rng.below(20) + 1);
+            t.insert(p, v);
+            values[p as usize] = Some(v);
+            continue;
+        }
+        let (a, b) = (rng.below(50), rng.below(50));
+        let (l, r) = (min(a, b), max(a, b));
+        // the leftmost of the largest values
+        let expected = (l..r + 1).filter_map(|p| values[p as usize].map(|v| (p, v)))
+            .fold(None, |best: Option<(i64, i64)>, (p, v)| match best {
+                Some((_, b)) if b >= v => best,
+                _ => Some((p, v)),
+            });
+        assert_eq!(t.pop_max(l, r), expected);
+        if let Some((p, _)) = expected {
+            values[p as usize] = None;
+        }
+        let all = values.iter().flatten().cloned().max().unwrap_or(0);
+        assert_eq!(t.query(0, 49), Some(all));
+    }
+}
+
+#[test]
+fn predecessor_and_successor() {
+    let mut t = PointSegmentTree::new(-100, 1_000_000, 0,
+                                      Box::new(|x: &i64, y: &i64| max(*x, *y)));
+    assert_eq!(t.first(), None);
+    assert_eq!(t.last(), None);
+    assert_eq!(t.successor(0), None);
+    t.insert(-50, 1);
+    t.insert(7, 0);
+    t.insert(8, 2);
+    t.insert(500_000, 3);
+    assert_eq!(t.first(), Some((-50, 1)));
+    assert_eq!(t.last(), Some((500_000, 3)));
+    assert_eq!(t.successor(-100), Some((-50, 1)));
+    assert_eq!(t.successor(-50), Some((-50, 1)));
+    assert_eq!(t.successor(-49), Some((7, 0)));
+    assert_eq!(t.successor(9), Some((500_000, 3)));
+    assert_eq!(t.successor(500_001), None);
+    assert_eq!(t.predecessor(-51), None);
+    assert_eq!(t.predecessor(7), Some((7, 0)));
+    assert_eq!(t.predecessor(499_999), Some((8, 2)));
+    assert_eq!(t.predecessor(1_000_000), Some((500_000, 3)));
+
+    assert_eq!(t.successor_non_default(-49), Some((8, 2)));
+    assert_eq!(t.predecessor_non_default(7), Some((-50, 1)));
+    assert_eq!(t.predecessor_non_default(-51), None);
+
+    assert_eq!(t.pop_max(0, 10), Some((8, 2)));
+    assert_eq!(t.successor(8), Some((500_000, 3)));
+    assert_eq!(t.predecessor(499_999), Some((7, 0)));
+}
+
+#[test]
+fn query_ranges_and_complement() {
+    let concat_f = Box::new(|x: &String, y: &String| x.clone() + y);
+    let mut t = PointSegmentTree::new(0, 25, "".to_string(), concat_f);
+    let letters = "abcdefghijklmnopqrstuvwxyz";
+    for (p, c) in letters.chars().enumerate() {
+        t.insert(p as i64, c.to_string());
+    }
+    assert_eq!(t.query_ranges(&[(0, 2), (10, 12)]), Some("abcklm".to_string()));
+    assert_eq!(t.query_ranges(&[(10, 12), (0, 2)]), Some("abcklm".to_string()));
+    assert_eq!(t.query_ranges(&[(3, 4), (0, 2), (5, 5)]), Some("abcdef".to_string()));
+    assert_eq!(t.query_ranges(&[(25, 25), (7, 7), (0, 0)]), Some("ahz".to_string()));
+    assert_eq!(t.query_ranges(&[]), Some("".to_string()));
+    assert_eq!(t.query_ranges(&[(0, 2), (2, 4)]), None);
+    assert_eq!(t.query_ranges(&[(0, 5), (2, 3)]), None);
+    assert_eq!(t.query_ranges(&[(0, 2), (5, 26)]), None);
+    assert_eq!(t.query_ranges(&[(3, 2)]), None);
+    assert_eq!(t.query_complement(3, 22), Some("abcxyz".to_string()));
+    assert_eq!(t.query_complement(0, 22), Some("xyz".to_string()));
+    assert_eq!(t.query_complement(1, 25), Some("a".to_string()));
+    assert_eq!(t.query_complement(0, 25), Some("".to_string()));
+    assert_eq!(t.query_complement(5, 4), None);
+    assert_eq!(t.query_complement(-1, 4), None);
+}
+
+#[test]
+fn query_ranges_matches_query() {
+    let mut t = PointSegmentTree::new(0, 1_000_000, 0,
+                                      Box::new(|x: &i64, y: &i64| x + y));
+    for i in 0..100 {
+        t.insert(i * 9_973, i);
+    }
+    let ranges = [(5, 50_000), (60_000, 60_000), (100_000, 499_999), (500_000, 999_999)];
+    let expected: i64 = ranges.iter().map(|r| t.query(r.0, r.1).unwrap()).sum();
+    assert_eq!(t.query_ranges(&ranges), Some(expected));
+    assert_eq!(t.query_complement(100_000, 499_999),
+               Some(t.query(0, 1_000_000).unwrap() - t.query(100_000, 499_999).unwrap()));
+}
+
+#[test]
+fn scan() {
+    let mut t = PointSegmentTree::new(0, 1_000_000, 0,
+                                      Box::new(|x: &i64, y: &i64| x + y));
+    assert_eq!(t.scan(0, 1_000_000).unwrap().collect::<Vec<_>>(),
+               vec![ScanItem::Unset(0, 1_000_000, 0)]);
+    t.insert(0, 5);
+    t.insert(1, 2);
+    t.insert(500, 3);
+    t.insert(999_999, 10);
+    assert_eq!(t.scan(0, 1_000_000).unwrap().collect::<Vec<_>>(),
+               vec![ScanItem::Point(0, 5),
+                    ScanItem::Point(1, 7),
+                    ScanItem::Unset(2, 499, 7),
+                    ScanItem::Point(500, 10),
+                    ScanItem::Unset(501, 999_998, 10),
+                    ScanItem::Point(999_999, 20),
+                    ScanItem::Unset(1_000_000, 1_000_000, 20)]);
+    assert_eq!(t.scan(1, 600).unwrap().collect::<Vec<_>>(),
+               vec![ScanItem::Point(1, 2),
+                    ScanItem::Unset(2, 499, 2),
+                    ScanItem::Point(500, 5),
+                    ScanItem::Unset(501, 600, 5)]);
+    assert_eq!(t.scan(2, 499).unwrap().collect::<Vec<_>>(),
+               vec![ScanItem::Unset(2, 499, 0)]);
+    assert_eq!(t.scan(500, 500).unwrap().collect::<Vec<_>>(),
+               vec![ScanItem::Point(500, 3)]);
+    assert!(t.scan(3, 2).is_none());
+    assert!(t.scan(0, 1_000_001).is_none());
+}
+
+#[test]
+fn scan_matches_query() {
+    let mut t = PointSegmentTree::new(-20, 43, 0,
+                                      Box::new(|x: &i64, y: &i64| x + y));
+    for &p in &[-20, -19, -7, 0, 1, 2, 16, 30, 31, 43] {
+        t.insert(p, p * p + 1);
+    }
+    for l in -20..44 {
+        for r in l..44 {
+            let mut next = l;
+            for item in t.scan(l, r).unwrap() {
+                let (start, end, acc) = match item {
+                    ScanItem::Point(p, acc) => (p, p, acc),
+                    ScanItem::Unset(s, e, acc) => (s, e, acc),
+                };
+                assert_eq!(start, next);
+                assert_eq!(t.query(l, end), Some(acc));
+                next = end + 1;
+            }
+            assert_eq!(next, r + 1);
+        }
+    }
+}
+
+#[test]
+fn map_range() {
+    let mut t = PointSegmentTree::new(0, 1_000_000, 0,
+                                      Box::new(|x: &i64, y: &i64| x + y));
+    for i in 0..10 {
+        t.insert(i * 100_000, 1 << i);
+    }
+    t.map_range(150_000, 650_000, |v| v * 10);
+    assert_eq!(t.query(0, 1_000_000), Some(1 + 2 + 10 * (4 + 8 + 16 + 32 + 64) + 128 + 256 + 512));
+    assert_eq!(t.query(200_000, 200_000), Some(40));
+    assert_eq!(t.query(700_000, 700_000), Some(128));
+    assert_eq!(t.query(150_001, 150_001), Some(0));
+
+    let mut calls = 0;
+    t.map_range(0, 1_000_000, |v| { calls += 1; v - 1 });
+    assert_eq!(calls, 10);
+    assert_eq!(t.query(0, 0), Some(0));
+    assert_eq!(t.query(900_000, 1_000_000), Some(511));
+    assert_eq!(t.successor(1), Some((100_000, 1)));
+}
+
+#[test]
+#[should_panic]
+fn invalid_map_range() {
+    let mut t = PointSegmentTree::new(0, 10, 0,
+                                      Box::new(|x: &i64, y: &i64| x + y));
+    t.map_range(5, 11, |v| *v);
+}
+
+#[test]
+fn decompose_and_walk() {
+    let mut t = PointSegmentTree::new(0, 15, 0, Box::new(|x: &i64, y: &i64| x + y));
+    for (p, v) in [(1, 1), (2, 10), (5, 100), (9, 1000), (14, 10000)] {
+        t.insert(p, v);
+    }
+    assert!(t.decompose(5, 4).is_none());
+    assert!(t.decompose(0, 16).is_none());
+
+    let nodes = t.decompose(2, 12).unwrap();
+    let spans: Vec<(i64, i64, bool)> = nodes.iter()
+        .map(|n| (*n.start(), *n.end(), n.is_allocated()))
+        .collect();
+    assert_eq!(spans, vec![(2, 3, true), (4, 7, true), (8, 11, true), (12, 12, false)]);
+    let total: i64 = nodes.iter().map(|n| *n.value()).sum();
+    assert_eq!(Some(total), t.query(2, 12));
+
+    let nodes = t.decompose(6, 8).unwrap();
+    let spans: Vec<(i64, i64, bool)> = nodes.iter()
+        .map(|n| (*n.start(), *n.end(), n.is_allocated()))
+        .collect();
+    assert_eq!(spans, vec![(6, 7, false), (8, 8, false)]);
+    assert!(nodes.iter().all(|n| *n.value() == 0));
+
+    let root = t.root();
+    assert_eq!((*root.start(), *root.end(), *root.value()), (0, 15, 11111));
+    let mut leaves = Vec::new();
+    root.visit(&mut |n| if n.is_leaf() && n.start() == n.end() {
+        leaves.push((*n.start(), *n.value()));
+    });
+    assert_eq!(leaves, vec![(1, 1), (2, 10), (5, 100), (9, 1000), (14, 10000)]);
+}
+
+#[test]
+fn merge_unions_points() {
+    let mut rng = Lcg::new(5);
+    let build = || PointSegmentTree::new(0, 63, 0, Box::new(|x: &i64, y: &i64| x + y));
+    let (mut a, mut b) = (build(), build());
+    let (mut naive_a, mut naive_b) = (vec![None; 64], vec![None; 64]);
+    for _ in 0..25 {
+        let (p, v) = (rng.below(64), rng.below(10));
+        a.insert(p, v);
+        naive_a[p as usize] = Some(v);
+        let (p, v) = (rng.below(64), rng.below(10));
+        b.insert(p, v);
+        naive_b[p as usize] = Some(v);
+    }
+    let naive: Vec<Option<i64>> = naive_a.iter().zip(naive_b.iter())
+        .map(|(x, y)| match (x, y) {
+            (Some(x), Some(y)) => Some(x * 100 + y),
+            _ => x.or(*y),
+        })
+        .collect();
+    let merged = a.merge(b, |x, y| x * 100 + y);
+    for _ in 0..50 {
+        let (x, y) = (rng.below(64), rng.below(64));
+        let (l, r) = (min(x, y), max(x, y));
+        let expected: i64 = naive[l as usize..r as usize + 1].iter().flatten().sum();
+        assert_eq!(merged.query(l, r), Some(expected));
+    }
+}
+
+#[test]
+#[should_panic]
+fn merge_different_bounds() {
+    let a = PointSegmentTree::new(0, 10, 0, Box::new(|x: &i64, y: &i64| x + y));
+    let b = PointSegmentTree::new(1, 10, 0, Box::new(|x: &i64, y: &i64| x + y));
+    let _ = a.merge(b, |x, y| x + y);
+}
+
+#[test]
+fn split_off_moves_points() {
+    let mut rng = Lcg::new(17);
+    for k in [0, 1, 20, 31, 32, 45, 62] {
+        let mut t = PointSegmentTree::new(0, 63, 0, Box::new(|x: &i64, y: &i64| x + y));
+        let mut naive = vec![0; 64];
+        for _ in 0..30 {
+            let (p, v) = (rng.below(64), rng.below(100) + 1);
+            t.insert(p, v);
+            naive[p as usize] = v;
+        }
+        let right = t.split_off(k);
+        assert_eq!(t.bounds(), (0, k));
+        assert_eq!(right.bounds(), (k + 1, 63));
+        assert_eq!(t.query(0, k + 1), None);
+        assert_eq!(right.query(k, 63), None);
+        for l in 0..k + 1 {
+            assert_eq!(t.query(l, k), Some(naive[l as usize..k as usize + 1].iter().sum()));
+        }
+        for r in k + 1..64 {
+            assert_eq!(right.query(k + 1, r), Some(naive[k as usize + 1..r as usize + 1].iter().sum()));
+        }
+        let last = naive[..k as usize + 1].iter().rposition(|v| *v > 0);
+        assert_eq!(t.last().map(|(p, _)| p as usize), last);
+        let first = naive[k as usize + 1..].iter().position(|v| *v > 0);
+        assert_eq!(right.first().map(|(p, _)| (p - k - 1) as usize), first);
+    }
+}
+
+#[test]
+#[should_panic]
+fn split_off_at_upper_bound() {
+    let mut t = PointSegmentTree::new(0, 10, 0, Box::new(|x: &i64, y: &i64| x + y));
+    let _ = t.split_off(10);
+}
@@ -1,6 +1,10 @@
 extern crate interval_tree;
 
+mod common;
+
+use common::{Lcg};
 use interval_tree::segmentpoint::{SegmentPointTree};
+use std::cmp::{max, min};
 
 #[test]
 fn queries_on_empty() {
@@ -75,7 +79,7 @@ fn small_queries_2() {
 
 #[test]
 fn small_queries_3() {
-    let concat_f = Box::new(|x: &String, y: &String| (x.clone() + &*y).to_string());
+    let concat_f = Box::new(|x: &String, y: &String| (x.clone() + y).to_string());
     let mut t:SegmentPointTree<i64, String> = SegmentPointTree::new(4, 6, "".to_string(), concat_f);
     assert_eq!(t.bounds(), (4, 6));
     t.insert(4, 5, "a".to_string());
@@ -128,3 +132,213 @@ fn large_queries() {
     assert_eq!(t.query(1_000_000), Some(0));
     assert_eq!(t.query(1_000_001), None);
 }
+
+#[test]
+fn ordered_latest_wins() {
+    let mut t = SegmentPointTree::new_ordered(0, 10, 0,
+                                              Box::new(|_: &i64, y: &i64| *y));
+    assert!(t.is_ordered());
+    t.insert(0, 1, 1);
+    t.insert(0, 10, 2);
+    t.insert(3, 5, 3);
+    t.insert(4, 4, 4);
+    t.insert(0, 7, 5);
+    let values = vec![5, 5, 5, 5, 5, 5, 5, 5, 2, 2, 2];
+    for (p, v) in (0..11).zip(values) {
+        assert_eq!(t.query(p), Some(v));
+    }
+    t.insert(5, 9, 6);
+    let values = vec![5, 5, 5, 5, 5, 6, 6, 6, 6, 6, 2];
+    for (p, v) in (0..11).zip(values) {
+        assert_eq!(t.query(p), Some(v));
+    }
+    assert_eq!(t.query(11), None);
+}
+
+#[test]
+fn ordered_composition() {
+    let concat_f = Box::new(|x: &String, y: &String| x.clone() + y);
+    let mut t = SegmentPointTree::new_ordered(0, 7, "".to_string(), concat_f);
+    t.insert(2, 2, "a".to_string());
+    t.insert(0, 7, "b".to_string());
+    t.insert(1, 3, "c".to_string());
+    t.insert(2, 5, "d".to_string());
+    let values = vec!["b", "bc", "abcd", "bcd", "bd", "bd", "b", "b"];
+    for (p, v) in (0..8).zip(values) {
+        assert_eq!(t.query(p), Some(v.to_string()));
+    }
+
+    // the same inserts in an unordered tree are folded root to leaf
+    let concat_f = Box::new(|x: &String, y: &String| x.clone() + y);
+    let mut u = SegmentPointTree::new(0, 7, "".to_string(), concat_f);
+    assert!(!u.is_ordered());
+    u.insert(2, 2, "a".to_string());
+    u.insert(0, 7, "b".to_string());
+    u.insert(1, 3, "c".to_string());
+    u.insert(2, 5, "d".to_string());
+    assert_eq!(u.query(2), Some("bcda".to_string()));
+}
+
+#[test]
+fn decompose_and_walk() {
+    let mut t = SegmentPointTree::new(0, 15, 0, Box::new(|x: &i64, y: &i64| x + y));
+    t.insert(2, 9, 1);
+    assert!(t.decompose(3, 2).is_none());
+    assert!(t.decompose(0, 16).is_none());
+
+    let spans: Vec<(i64, i64, i64)> = t.decompose(2, 9).unwrap().iter()
+        .map(|n| (*n.start(), *n.end(), *n.value()))
+        .collect();
+    assert_eq!(spans, vec![(2, 3, 1), (4, 7, 1), (8, 9, 1)]);
+    let spans: Vec<(i64, i64)> = t.decompose(0, 15).unwrap().iter()
+        .map(|n| (*n.start(), *n.end()))
+        .collect();
+    assert_eq!(spans, vec![(0, 15)]);
+
+    // nodes an insert would still have to create are reported as well
+    let spans: Vec<(i64, i64, i64, bool)> = t.decompose(2, 12).unwrap().iter()
+        .map(|n| (*n.start(), *n.end(), *n.value(), n.is_allocated()))
+        .collect();
+    assert_eq!(spans, vec![(2, 3, 1, true), (4, 7, 1, true), (8, 11, 0, true),
+                           (12, 12, 0, false)]);
+    let mut u = SegmentPointTree::new(0, 15, 0, Box::new(|x: &i64, y: &i64| x + y));
+    u.insert(2, 9, 1);
+    u.insert(2, 12, 10);
+    let spans: Vec<(i64, i64, i64, bool)> = u.decompose(2, 12).unwrap().iter()
+        .map(|n| (*n.start(), *n.end(), *n.value(), n.is_allocated()))
+        .collect();
+    assert_eq!(spans, vec![(2, 3, 11, true), (4, 7, 11, true), (8, 11, 10, true),
+                           (12, 12, 10, true)]);
+
+    let mut count = 0;
+    t.root().visit(&mut |n| {
+        assert!(n.start() <= n.end());
+        count += 1;
+    });
+    assert_eq!(count, 8);
+    assert!(t.root().right().unwrap().right().is_none());
+}
+
+#[test]
+fn runs_and_dense_vec() {
+    let mut t = SegmentPointTree::new(0, 20, 0, Box::new(|x: &i64, y: &i64| x + y));
+    assert_eq!(t.to_runs(0, 20), Some(vec![(0, 20, 0)]));
+    assert_eq!(t.to_runs(5, 4), None);
+    assert_eq!(t.to_dense_vec(0, 21), None);
+
+    t.insert(3, 7, 1);
+    t.insert(8, 12, 1);
+    t.insert(10, 15, 2);
+    assert_eq!(t.to_runs(0, 20), Some(vec![(0, 2, 0), (3, 9, 1), (10, 12, 3),
+                                           (13, 15, 2), (16, 20, 0)]));
+    assert_eq!(t.to_runs(11, 13), Some(vec![(11, 12, 3), (13, 13, 2)]));
+    assert_eq!(t.to_dense_vec(1, 4), Some(vec![0, 0, 1, 1]));
+}
+
+#[test]
+fn runs_match_queries() {
+    let mut rng = Lcg::new(7);
+    for ordered in [false, true] {
+        let combine = Box::new(|x: &i64, y: &i64| x * 3 + y);
+        let mut t = if ordered {
+            SegmentPointTree::new_ordered(-10, 50, 1, combine)
+        } else {
+            SegmentPointTree::new(-10, 50, 1, combine)
+        };
+        for _ in 0..30 {
+            let a = rng.below(61) - 10;
+            let b = rng.below(61) - 10;
+            t.insert(min(a, b), max(a, b), rng.below(4));
+        }
+        for _ in 0..20 {
+            let a = rng.below(61) - 10;
+            let b = rng.below(61) - 10;
+            let (l, r) = (min(a, b), max(a, b));
+            let expected: Vec<i64> = (l..r + 1).map(|p| t.query(p).unwrap()).collect();
+            assert_eq!(t.to_dense_vec(l, r), Some(expected.clone()));
+            let runs = t.to_runs(l, r).unwrap();
+            let mut dense = Vec::new();
+            for (i, &(s, e, v)) in runs.iter().enumerate() {
+                if i > 0 {
+                    assert_eq!(runs[i - 1].1 + 1, s);
+                    assert!(runs[i - 1].2 != v);
+                }
+                dense.extend((s..e + 1).map(|_| v));
+            }
+            assert_eq!(dense, expected);
+        }
+    }
+}
+
+#[test]
+fn merge_adds_segments() {
+    let mut a = SegmentPointTree::new(0, 10, 0, Box::new(|x: &i64, y: &i64| x + y));
+    let mut b = SegmentPointTree::new(0, 10, 0, Box::new(|x: &i64, y: &i64| x + y));
+    a.insert(0, 5, 1);
+    a.insert(3, 3, 10);
+    b.insert(2, 9, 100);
+    let merged = a.merge(b);
+    assert_eq!(merged.to_runs(0, 10), Some(vec![(0, 1, 1), (2, 2, 101), (3, 3, 111),
+                                                (4, 5, 101), (6, 9, 100), (10, 10, 0)]));
+}
+
+#[test]
+fn merge_ordered_appends() {
+    let mut rng = Lcg::new(11);
+    let build = || SegmentPointTree::new_ordered(0, 40, 0, Box::new(|x: &i64, y: &i64| x * 3 + y));
+    let (mut a, mut b, mut all) = (build(), build(), build());
+    let mut later = Vec::new();
+    for i in 0..20 {
+        let (x, y) = (rng.below(41), rng.below(41));
+        let (s, e, v) = (min(x, y), max(x, y), rng.below(5));
+        if i % 2 == 0 {
+            a.insert(s, e, v);
+            all.insert(s, e, v);
+        } else {
+            b.insert(s, e, v);
+            later.push((s, e, v));
+        }
+    }
+    // the segments of the merged-in tree count as inserted last
+    for (s, e, v) in later {
+        all.insert(s, e, v);
+    }
+    let merged = a.merge(b);
+    assert_eq!(merged.to_dense_vec(0, 40), all.to_dense_vec(0, 40));
+}
+
+#[test]
+#[should_panic]
+fn merge_different_bounds() {
+    let a = SegmentPointTree::new(0, 10, 0, Box::new(|x: &i64, y: &i64| x + y));
+    let b = SegmentPointTree::new(0, 11, 0, Box::new(|x: &i64, y: &i64| x + y));
+    let _ = a.merge(b);
+}
+
+#[test]
+fn split_off_keeps_values() {
+    let mut rng = Lcg::new(23);
+    for ordered in [false, true] {
+        let mut t = if ordered {
+            SegmentPointTree::new_ordered(0, 50, 0, Box::new(|x: &i64, y: &i64| x * 3 + y))
+        } else {
+            SegmentPointTree::new(0, 50, 1, Box::new(|x: &i64, y: &i64| x + y))
+        };
+        for _ in 0..25 {
+            let (x, y) = (rng.below(51), rng.below(51));
+            t.insert(min(x, y), max(x, y), rng.below(5));
+        }
+        let before = t.to_dense_vec(0, 50).unwrap();
+        let mut right = t.split_off(17);
+        assert_eq!(t.bounds(), (0, 17));
+        assert_eq!(right.bounds(), (18, 50));
+        assert_eq!(t.query(18), None);
+        assert_eq!(right.query(17), None);
+        assert_eq!(t.to_dense_vec(0, 17).unwrap(), before[..18].to_vec());
+        assert_eq!(right.to_dense_vec(18, 50).unwrap(), before[18..].to_vec());
+
+        // the trees stay independent
+        right.insert(18, 50, 4);
+        assert_eq!(t.to_dense_vec(0, 17).unwrap(), before[..18].to_vec());
+    }
+}
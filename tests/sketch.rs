@@ -0,0 +1,94 @@
+extern crate interval_tree;
+
+mod common;
+
+use common::{Lcg};
+use interval_tree::pointsegment::{PointSegmentTree};
+use interval_tree::sketch::{HyperLogLog, Kll};
+use std::collections::HashSet;
+
+#[test]
+fn hyperloglog_small_and_identity() {
+    let e = HyperLogLog::identity();
+    assert_eq!(e.estimate(), 0.);
+    let mut s = HyperLogLog::new(10);
+    for x in 0..10 {
+        s.insert(&x);
+        s.insert(&x);
+    }
+    assert!((s.estimate() - 10.).abs() < 1.);
+    assert_eq!(HyperLogLog::combine(&e, &s), s);
+    assert_eq!(HyperLogLog::combine(&s, &s), s);
+}
+
+#[test]
+#[should_panic]
+fn hyperloglog_invalid_precision() {
+    let _ = HyperLogLog::new(20);
+}
+
+#[test]
+fn hyperloglog_distinct_in_ranges() {
+    let n = 4000;
+    let mut rng = Lcg::new(11);
+    let values: Vec<u64> = (0..n).map(|_| rng.next_u64() % 3000).collect();
+    let mut t = PointSegmentTree::new(0, n as i64 - 1, HyperLogLog::identity(),
+                                      Box::new(HyperLogLog::combine));
+    for (p, v) in values.iter().enumerate() {
+        t.insert(p as i64, HyperLogLog::of(12, v));
+    }
+    for &(l, r) in &[(0, n - 1), (0, 99), (500, 2499), (1234, 3999), (7, 7)] {
+        let exact = values[l..r + 1].iter().collect::<HashSet<_>>().len() as f64;
+        let estimate = t.query(l as i64, r as i64).unwrap().estimate();
+        assert!((estimate - exact).abs() <= 0.05 * exact + 1.,
+                "range {:?}: estimate {} exact {}", (l, r), estimate, exact);
+    }
+}
+
+#[test]
+fn kll_exact_while_small() {
+    let mut s = Kll::new(64);
+    assert_eq!(s.quantile(0.5), None);
+    for x in 1..41 {
+        s.insert(x as f64);
+    }
+    assert_eq!(s.count(), 40);
+    assert_eq!(s.quantile(0.), Some(1.));
+    assert_eq!(s.quantile(0.5), Some(20.));
+    assert_eq!(s.quantile(1.), Some(40.));
+    assert_eq!(s.rank(10.5), 10);
+    assert_eq!(Kll::combine(&Kll::identity(), &s), s);
+}
+
+#[test]
+#[should_panic(expected = "Can't insert NaN")]
+fn kll_rejects_nan() {
+    let mut s = Kll::new(8);
+    s.insert(1.);
+    s.insert(f64::NAN);
+}
+
+#[test]
+fn kll_quantiles_in_ranges() {
+    let n = 5000;
+    let mut rng = Lcg::new(17);
+    let values: Vec<f64> = (0..n).map(|_| rng.below(100_000) as f64).collect();
+    let mut t = PointSegmentTree::new(0, n as i64 - 1, Kll::identity(),
+                                      Box::new(Kll::combine));
+    for (p, v) in values.iter().enumerate() {
+        t.insert(p as i64, Kll::of(200, *v));
+    }
+    for &(l, r) in &[(0, n - 1), (0, 999), (1000, 4321), (2500, 2600)] {
+        let sketch = t.query(l as i64, r as i64).unwrap();
+        let mut exact: Vec<f64> = values[l..r + 1].to_vec();
+        exact.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let count = exact.len();
+        assert_eq!(sketch.count(), count as u64);
+        for &q in &[0.01, 0.1, 0.25, 0.5, 0.75, 0.9, 0.99] {
+            let estimate = sketch.quantile(q).unwrap();
+            let rank = exact.iter().filter(|v| **v <= estimate).count() as f64;
+            let error = (rank / count as f64 - q).abs();
+            assert!(error < 0.03, "range {:?} q {}: rank error {}", (l, r), q, error);
+        }
+    }
+}
@@ -0,0 +1,82 @@
+extern crate interval_tree;
+
+use interval_tree::pointsegment::{PointSegmentTree};
+use interval_tree::segmentpoint::{SegmentPointTree};
+use interval_tree::stats::{Stats};
+
+fn close(x: f64, y: f64) -> bool {
+    (x - y).abs() <= 1e-9 * (1. + x.abs().max(y.abs()))
+}
+
+fn check(stats: &Stats, values: &[f64]) {
+    assert_eq!(stats.count(), values.len() as u64);
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let variance = values.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / n;
+    assert!(close(stats.mean().unwrap(), mean));
+    assert!(close(stats.sum(), mean * n));
+    assert!(close(stats.variance().unwrap(), variance));
+    assert!(close(stats.std_dev().unwrap(), variance.sqrt()));
+    assert_eq!(stats.min(), values.iter().cloned().fold(None, |m, v| Some(m.map_or(v, |m: f64| m.min(v)))));
+    assert_eq!(stats.max(), values.iter().cloned().fold(None, |m, v| Some(m.map_or(v, |m: f64| m.max(v)))));
+    if values.len() > 1 {
+        assert!(close(stats.sample_variance().unwrap(), variance * n / (n - 1.)));
+    } else {
+        assert_eq!(stats.sample_variance(), None);
+    }
+}
+
+#[test]
+fn empty_and_single() {
+    let e = Stats::identity();
+    assert_eq!(e.count(), 0);
+    assert_eq!(e.mean(), None);
+    assert_eq!(e.variance(), None);
+    assert_eq!(e.min(), None);
+    assert_eq!(e.max(), None);
+    assert_eq!(Stats::combine(&e, &Stats::of(2.5)), Stats::of(2.5));
+    let mut s = Stats::identity();
+    s.push(4.);
+    s.push(8.);
+    check(&s, &[4., 8.]);
+}
+
+#[test]
+fn point_segment_ranges() {
+    let values: Vec<f64> = (0..50).map(|i| ((i * 37) % 23) as f64 * 1.5 - 7.).collect();
+    let mut t = PointSegmentTree::new(0, 49, Stats::identity(), Box::new(Stats::combine));
+    for (p, v) in values.iter().enumerate() {
+        t.insert(p as i64, Stats::of(*v));
+    }
+    for l in 0..50 {
+        for r in l..50 {
+            check(&t.query(l as i64, r as i64).unwrap(), &values[l..r + 1]);
+        }
+    }
+}
+
+#[test]
+fn segment_point_covering_segments() {
+    let mut t = SegmentPointTree::new(0, 10, Stats::identity(), Box::new(Stats::combine));
+    t.insert(0, 10, Stats::of(1.));
+    t.insert(2, 5, Stats::of(4.));
+    t.insert(4, 8, Stats::of(10.));
+    check(&t.query(0).unwrap(), &[1.]);
+    check(&t.query(3).unwrap(), &[1., 4.]);
+    check(&t.query(4).unwrap(), &[1., 4., 10.]);
+    check(&t.query(7).unwrap(), &[1., 10.]);
+    assert_eq!(t.query(11), None);
+}
+
+#[test]
+fn stable_with_large_offset() {
+    let values: Vec<f64> = (0..1000).map(|i| 1e9 + (i % 7) as f64).collect();
+    let mut t = PointSegmentTree::new(0, 999, Stats::identity(), Box::new(Stats::combine));
+    for (p, v) in values.iter().enumerate() {
+        t.insert(p as i64, Stats::of(*v));
+    }
+    let s = t.query(0, 999).unwrap();
+    let mean = values.iter().map(|v| v - 1e9).sum::<f64>() / 1000.;
+    let variance = values.iter().map(|v| (v - 1e9 - mean).powi(2)).sum::<f64>() / 1000.;
+    assert!((s.variance().unwrap() - variance).abs() < 1e-6);
+}
@@ -0,0 +1,119 @@
+extern crate interval_tree;
+
+mod common;
+
+use common::{Lcg};
+use interval_tree::pointsegment::{PointSegmentTree};
+use interval_tree::summary::{MaxSubarray, EqualRun, IncreasingRun};
+
+fn naive_max_subarray(values: &[i64]) -> Option<i64> {
+    let mut best = None;
+    for i in 0..values.len() {
+        let mut sum = 0;
+        for v in &values[i..] {
+            sum += *v;
+            best = Some(best.map_or(sum, |b: i64| b.max(sum)));
+        }
+    }
+    best
+}
+
+fn naive_longest_run(values: &[i64], joined: &dyn Fn(i64, i64) -> bool) -> usize {
+    let mut best = 0;
+    let mut current = 0;
+    for i in 0..values.len() {
+        current = if i > 0 && joined(values[i - 1], values[i]) { current + 1 } else { 1 };
+        best = best.max(current);
+    }
+    best
+}
+
+fn pseudo_random_values(n: usize, modulo: i64) -> Vec<i64> {
+    let mut rng = Lcg::new(12345);
+    (0..n).map(|_| rng.below(modulo) - modulo / 2).collect()
+}
+
+#[test]
+fn max_subarray() {
+    let mut t = PointSegmentTree::new(0, 9, MaxSubarray::identity(),
+                                      Box::new(MaxSubarray::combine));
+    assert_eq!(t.query(0, 9).unwrap().best(), None);
+    let values = vec![2, -3, 4, -1, 2, 1, -5, 4, -10, 3];
+    for (p, v) in values.iter().enumerate() {
+        t.insert(p as i64, MaxSubarray::of(*v));
+    }
+    let all = t.query(0, 9).unwrap();
+    assert_eq!(all.best(), Some(6));
+    assert_eq!(all.prefix(), Some(5));
+    assert_eq!(all.suffix(), Some(3));
+    assert_eq!(all.total(), -3);
+    assert_eq!(all.len(), 10);
+    assert_eq!(t.query(6, 6).unwrap().best(), Some(-5));
+    assert_eq!(t.query(6, 8).unwrap().best(), Some(4));
+    assert_eq!(t.query(0, 1).unwrap().best(), Some(2));
+}
+
+#[test]
+fn max_subarray_matches_naive() {
+    let values = pseudo_random_values(40, 21);
+    let mut t = PointSegmentTree::new(0, 39, MaxSubarray::identity(),
+                                      Box::new(MaxSubarray::combine));
+    for (p, v) in values.iter().enumerate() {
+        t.insert(p as i64, MaxSubarray::of(*v));
+    }
+    for l in 0..40 {
+        for r in l..40 {
+            assert_eq!(t.query(l as i64, r as i64).unwrap().best(),
+                       naive_max_subarray(&values[l..r + 1]));
+        }
+    }
+}
+
+#[test]
+fn runs_match_naive() {
+    let values = pseudo_random_values(40, 3);
+    let mut eq = PointSegmentTree::new(0, 39, EqualRun::identity(),
+                                       Box::new(EqualRun::combine));
+    let mut inc = PointSegmentTree::new(0, 39, IncreasingRun::identity(),
+                                        Box::new(IncreasingRun::combine));
+    for (p, v) in values.iter().enumerate() {
+        eq.insert(p as i64, EqualRun::of(p as i64, *v));
+        inc.insert(p as i64, IncreasingRun::of(p as i64, *v));
+    }
+    for l in 0..40 {
+        for r in l..40 {
+            let slice = &values[l..r + 1];
+            assert_eq!(eq.query(l as i64, r as i64).unwrap().best(),
+                       naive_longest_run(slice, &|x, y| x == y));
+            assert_eq!(inc.query(l as i64, r as i64).unwrap().best(),
+                       naive_longest_run(slice, &|x, y| x < y));
+        }
+    }
+}
+
+#[test]
+fn runs_break_at_unset_points() {
+    let mut t = PointSegmentTree::new(0, 100, IncreasingRun::identity(),
+                                      Box::new(IncreasingRun::combine));
+    t.insert(3, IncreasingRun::of(3, 1));
+    t.insert(50, IncreasingRun::of(50, 2));
+    t.insert(51, IncreasingRun::of(51, 3));
+    t.insert(53, IncreasingRun::of(53, 4));
+    t.insert(99, IncreasingRun::of(99, 5));
+    let all = t.query(0, 100).unwrap();
+    assert_eq!(all.best(), 2);
+    assert_eq!(all.prefix(), 1);
+    assert_eq!(all.suffix(), 1);
+    assert_eq!(all.len(), 5);
+    assert_eq!(t.query(50, 51).unwrap().best(), 2);
+    assert_eq!(t.query(50, 53).unwrap().best(), 2);
+    assert!(t.query(4, 49).unwrap().is_empty());
+
+    let mut eq = PointSegmentTree::new(0, 100, EqualRun::identity(),
+                                       Box::new(EqualRun::combine));
+    eq.insert(10, EqualRun::of(10, 7));
+    eq.insert(12, EqualRun::of(12, 7));
+    assert_eq!(eq.query(0, 100).unwrap().best(), 1);
+    eq.insert(11, EqualRun::of(11, 7));
+    assert_eq!(eq.query(0, 100).unwrap().best(), 3);
+}